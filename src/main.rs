@@ -1,50 +1,103 @@
 use battery::{Manager, State};
 use serde::de::{self, Deserializer, Visitor};
-use serde::Deserialize;
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs;
-use sysinfo::System;
+use std::io::{self, IsTerminal, Read, Write};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{
+    Components, Disk, Disks, Networks, ProcessStatus, ProcessesToUpdate, System, ThreadKind,
+    MINIMUM_CPU_UPDATE_INTERVAL,
+};
+
+/// Parse os-release `content` into its `KEY=value` pairs, with quotes stripped.
+fn parse_os_release(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.to_string(), value.trim_matches('"').to_string()))
+        })
+        .collect()
+}
 
 /// Attempt to read the human-friendly distribution name from `/etc/os-release`.
+/// With `detailed`, the release codename (`Ubuntu 22.04.3 LTS (Jammy)`) and
+/// build id are appended when present; rolling releases often have neither.
 /// Falls back to `None` when the information is unavailable.
-fn get_linux_distribution() -> Option<String> {
-    if let Ok(content) = fs::read_to_string("/etc/os-release") {
-        for line in content.lines() {
-            if line.starts_with("PRETTY_NAME=") {
-                return Some(
-                    line.trim_start_matches("PRETTY_NAME=")
-                        .trim_matches('"')
-                        .to_string(),
-                );
-            }
+fn get_linux_distribution(detailed: bool) -> Option<String> {
+    let content = fs::read_to_string("/etc/os-release").ok()?;
+    distribution_name(&parse_os_release(&content), detailed)
+}
+
+/// The distribution name described by parsed os-release pairs, as shown by
+/// [`get_linux_distribution`].
+fn distribution_name(release: &HashMap<String, String>, detailed: bool) -> Option<String> {
+    let mut name = release.get("PRETTY_NAME")?.clone();
+    if !detailed {
+        return Some(name);
+    }
+    if let Some(codename) = release.get("VERSION_CODENAME").filter(|c| !c.is_empty()) {
+        // Some distros already include the codename, e.g. `Debian GNU/Linux 12 (bookworm)`.
+        if !name.to_lowercase().contains(&codename.to_lowercase()) {
+            let mut chars = codename.chars();
+            let capitalized: String = chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default();
+            name = format!("{} ({})", name, capitalized);
         }
     }
-    None
+    if let Some(build_id) = release.get("BUILD_ID").filter(|b| !b.is_empty()) {
+        name = format!("{} build {}", name, build_id);
+    }
+    Some(name)
+}
+
+/// Charge level of a battery alongside its human-readable summary.
+struct BatteryReading {
+    percentage: f32,
+    description: String,
 }
 
 /// Compose a one-line summary of the first detected battery, including charge,
-/// state, and an ETA if the driver exposes it.
-fn get_battery_info() -> Result<String, Box<dyn std::error::Error>> {
+/// state (as a word, symbol, or both per `style`), and an ETA if the driver
+/// exposes it. Returns `None` without a battery.
+fn get_battery_info(
+    style: BatteryStateStyle,
+) -> Result<Option<BatteryReading>, Box<dyn std::error::Error>> {
     // Initialize battery manager
     let manager = Manager::new()?;
 
     // Get batteries iterator
-    let batteries = manager.batteries()?;
+    let mut batteries = manager.batteries()?;
 
     // Try to get the first battery
-    for battery in batteries {
+    if let Some(battery) = batteries.next() {
         let battery = battery?;
 
         // Get percentage (0.0 to 1.0)
         let percentage = battery.state_of_charge().value * 100.0;
 
         // Get battery state (charging, discharging, full, etc.)
-        let state = match battery.state() {
-            State::Charging => "Charging",
-            State::Discharging => "Discharging",
-            State::Full => "Full",
-            State::Empty => "Empty",
-            _ => "Unknown",
+        let (word, symbol) = match battery.state() {
+            State::Charging => ("Charging", "↑"),
+            State::Discharging => ("Discharging", "↓"),
+            State::Full => ("Full", "="),
+            State::Empty => ("Empty", "!"),
+            _ => ("Unknown", "?"),
+        };
+        let state = match style {
+            BatteryStateStyle::Word => format!(" ({})", word),
+            BatteryStateStyle::Symbol => format!(" {}", symbol),
+            BatteryStateStyle::Both => format!(" {} ({})", symbol, word),
         };
 
         // Get time to full/empty if available
@@ -72,169 +125,5648 @@ fn get_battery_info() -> Result<String, Box<dyn std::error::Error>> {
             String::new()
         };
 
-        return Ok(format!(
-            "Battery: {}% ({}){}",
-            percentage as u8, state, time_string
-        ));
+        return Ok(Some(BatteryReading {
+            percentage,
+            description: format!("{}%{}{}", percentage as u8, state, time_string),
+        }));
     }
 
     // No batteries found
-    Ok("Battery: Not detected".to_string())
+    Ok(None)
 }
 
-/// Convert the total physical memory reported in bytes to whole gigabytes.
-fn get_total_memory_gb(system: &System) -> u64 {
+/// Convert the total physical memory reported in bytes to whole gigabytes,
+/// stepping by the configured `memory_base`.
+fn get_total_memory_gb(system: &System, units: &UnitsConfig) -> u64 {
     let total_memory_bt = system.total_memory();
-    let total_memory_kb = total_memory_bt / 1024;
-    let total_memory_mb = total_memory_kb / 1024;
-    total_memory_mb / 1024
+    let total_memory_kb = total_memory_bt / units.memory_base;
+    let total_memory_mb = total_memory_kb / units.memory_base;
+    total_memory_mb / units.memory_base
 }
 
-/// Format a concise uptime string in the form `Xd Xh Xm`.
-fn format_uptime() -> String {
+/// Format a concise uptime string in the form `Xd Xh Xm`, or just the largest
+/// unit (`~3 days`) for the approximate format.
+fn format_uptime(format: UptimeFormat) -> String {
     let uptime_seconds = System::uptime();
-    let days = uptime_seconds / 86_400;
-    let hours = (uptime_seconds % 86_400) / 3_600;
-    let minutes = (uptime_seconds % 3_600) / 60;
-    let mut uptime_parts = Vec::new();
+    if format == UptimeFormat::Approximate {
+        let (amount, unit) = if uptime_seconds >= 86_400 {
+            (uptime_seconds / 86_400, "day")
+        } else if uptime_seconds >= 3_600 {
+            (uptime_seconds / 3_600, "hour")
+        } else {
+            (uptime_seconds / 60, "minute")
+        };
+        let plural = if amount == 1 { "" } else { "s" };
+        return format!("~{} {}{}", amount, unit, plural);
+    }
+    format_duration(uptime_seconds)
+}
+
+/// Format a span of seconds as `Xd Xh Xm`, leaving out leading zero units.
+fn format_duration(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let hours = (seconds % 86_400) / 3_600;
+    let minutes = (seconds % 3_600) / 60;
+    let mut parts = Vec::new();
     if days > 0 {
-        uptime_parts.push(format!("{}d", days));
+        parts.push(format!("{}d", days));
     }
-    if hours > 0 || !uptime_parts.is_empty() {
-        uptime_parts.push(format!("{}h", hours));
+    if hours > 0 || !parts.is_empty() {
+        parts.push(format!("{}h", hours));
     }
-    uptime_parts.push(format!("{}m", minutes));
-    uptime_parts.join(" ")
+    parts.push(format!("{}m", minutes));
+    parts.join(" ")
+}
+
+/// Format the local date and time with the strftime-style `format`, e.g.
+/// `2024-06-01 14:32` for the default `%Y-%m-%d %H:%M`.
+fn get_current_time(format: &str) -> String {
+    chrono::Local::now().format(format).to_string()
 }
 
-/// Report the first CPU's brand string and frequency (GHz).
-fn get_cpu_info(system: &System) -> Option<String> {
+/// Report the first CPU's brand string and frequency (GHz). With `round`,
+/// the frequency is rounded to the nearest 0.1 GHz so a measured 2594 MHz
+/// reads `2.6 GHz`, matching spec sheets.
+fn get_cpu_info(system: &System, round: bool, units: &UnitsConfig) -> Option<String> {
     system.cpus().first().map(|cpu| {
-        let brand = cpu.brand();
-        let frequency_mhz = cpu.frequency();
         format!(
-            "CPU Model: {} @ {:.2} GHz",
-            brand,
-            frequency_mhz as f64 / 1000.0
+            "{} @ {}",
+            cpu.brand(),
+            format_ghz(cpu.frequency(), round, units)
         )
     })
 }
 
+/// Format a clock given in MHz as GHz, e.g. `2.59 GHz`, or `2.6 GHz` with
+/// `round`.
+fn format_ghz(mhz: u64, round: bool, units: &UnitsConfig) -> String {
+    let ghz = mhz as f64 / 1000.0;
+    if round {
+        format!("{} GHz", units.decimal((ghz * 10.0).round() / 10.0, 1))
+    } else {
+        format!("{} GHz", units.decimal(ghz, 2))
+    }
+}
+
+/// Report CPU 0's advertised base and boost clocks, e.g.
+/// `@ 3.0 GHz (boost 4.2 GHz)`. The base comes from cpufreq's `base_frequency`
+/// or `bios_limit`, falling back to the `@ 3.00GHz` in the model name; the
+/// boost is the hardware maximum. Returns `None` without cpufreq.
+fn get_cpu_clocks(units: &UnitsConfig) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let cpufreq = Path::new("/sys/devices/system/cpu/cpu0/cpufreq");
+    // cpufreq reports kHz.
+    let read_ghz = |file: &str| -> Option<f64> {
+        let khz: f64 = fs::read_to_string(cpufreq.join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()?;
+        (khz > 0.0).then(|| khz / 1_000_000.0)
+    };
+    let boost = read_ghz("cpuinfo_max_freq").or_else(|| read_ghz("scaling_max_freq"))?;
+    let base = read_ghz("base_frequency")
+        .or_else(|| read_ghz("bios_limit"))
+        .or_else(|| {
+            let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+            let model = cpuinfo.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == "model name").then_some(value)
+            })?;
+            model
+                .rsplit_once('@')?
+                .1
+                .trim()
+                .trim_end_matches("GHz")
+                .parse()
+                .ok()
+        });
+    let ghz = |value: f64| units.decimal(value, 1);
+    Some(match base {
+        Some(base) if boost > base => format!("@ {} GHz (boost {} GHz)", ghz(base), ghz(boost)),
+        Some(base) => format!("@ {} GHz", ghz(base)),
+        None => format!("boost {} GHz", ghz(boost)),
+    })
+}
+
+/// Sum the threads of every process. Only Linux exposes per-process task lists,
+/// so other platforms report `None` rather than a misleading process count.
+fn get_thread_count(system: &System) -> Option<String> {
+    let task_counts = system
+        .processes()
+        .values()
+        // Userland threads show up as processes of their own; count them via
+        // their parent. Kernel threads have no parent process and count once.
+        .filter(|process| process.thread_kind() != Some(ThreadKind::Userland))
+        .map(|process| process.tasks().map(HashSet::len));
+    sum_threads(task_counts).map(|total| total.to_string())
+}
+
+/// Total threads given each process's task count, or `None` where the
+/// platform has no task list. The task list leaves out the main thread, so
+/// every process adds one more. `None` when no process had a task list.
+fn sum_threads(task_counts: impl IntoIterator<Item = Option<usize>>) -> Option<usize> {
+    task_counts
+        .into_iter()
+        .flatten()
+        .fold(None, |total, tasks| Some(total.unwrap_or(0) + tasks + 1))
+}
+
+/// Count processes stuck in the zombie (defunct) state, waiting for a parent
+/// to reap them. Needs a refreshed process table.
+fn get_zombie_count(system: &System) -> Option<String> {
+    if system.processes().is_empty() {
+        return None;
+    }
+    let zombies = system
+        .processes()
+        .values()
+        .filter(|process| process.status() == ProcessStatus::Zombie)
+        .count();
+    Some(zombies.to_string())
+}
+
+/// Name the process using the most memory with its resident size, e.g.
+/// `chrome (1.2 Gb)`. Needs a refreshed process table.
+fn get_top_memory_process(system: &System, units: &UnitsConfig) -> Option<String> {
+    let process = system
+        .processes()
+        .values()
+        // Threads report their whole process's memory.
+        .filter(|process| process.thread_kind().is_none())
+        .max_by_key(|process| process.memory())?;
+    Some(format!(
+        "{} ({} {})",
+        process.name().to_string_lossy(),
+        units.decimal(units.gigabytes(process.memory()), 1),
+        units.memory_label
+    ))
+}
+
+/// Count the CPUs in a kernel CPU list such as `0-3,6,8-11`.
+fn count_cpu_list(list: &str) -> Option<usize> {
+    let mut count = 0;
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        count += match range.split_once('-') {
+            Some((first, last)) => {
+                let first: usize = first.parse().ok()?;
+                let last: usize = last.parse().ok()?;
+                last.checked_sub(first)? + 1
+            }
+            None => {
+                range.parse::<usize>().ok()?;
+                1
+            }
+        };
+    }
+    Some(count)
+}
+
+/// Count the distinct groups of CPUs sharing an L3 cache, from each CPU's
+/// `cache/index3/shared_cpu_list`. Returns `None` when no L3 is described.
+fn get_cache_domains() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let domains: HashSet<String> = fs::read_dir("/sys/devices/system/cpu")
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            name.to_str()
+                .and_then(|name| name.strip_prefix("cpu"))
+                .is_some_and(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+        })
+        .filter_map(|entry| {
+            fs::read_to_string(entry.path().join("cache/index3/shared_cpu_list"))
+                .ok()
+                .map(|list| list.trim().to_string())
+        })
+        .collect();
+    (!domains.is_empty()).then(|| domains.len().to_string())
+}
+
+/// Compare the online CPUs against those present to show how many the kernel
+/// has parked (taken offline), e.g. `6 active, 2 parked`.
+fn get_cores_active() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let read = |file: &str| {
+        fs::read_to_string(Path::new("/sys/devices/system/cpu").join(file))
+            .ok()
+            .and_then(|list| count_cpu_list(&list))
+    };
+    let online = read("online")?;
+    let present = read("present").unwrap_or(online).max(online);
+    Some(format!("{} active, {} parked", online, present - online))
+}
+
+/// Hottest reading across all temperature sensors, in °C.
+fn get_max_temperature() -> Option<f32> {
+    Components::new_with_refreshed_list()
+        .iter()
+        .filter_map(|component| component.temperature())
+        .filter(|temperature| temperature.is_finite())
+        .reduce(f32::max)
+}
+
+/// Insert the configured thousands separator into a whole number.
+fn format_number(value: u64, grouping: NumberGrouping) -> String {
+    let digits = value.to_string();
+    let separator = match grouping {
+        NumberGrouping::None => return digits,
+        NumberGrouping::Comma => ',',
+        NumberGrouping::Space => ' ',
+    };
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Take a second CPU reading after the minimum update interval so
+/// `global_cpu_usage` reflects real load rather than the first-sample zero.
+fn sample_cpu_usage(system: &mut System) -> f32 {
+    thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_cpu_usage();
+    system.global_cpu_usage()
+}
+
+/// Draw each core's usage as one block character, from `▁` (idle) to `█`
+/// (fully busy). Relies on the same two-sample refresh as `sample_cpu_usage`.
+fn get_cpu_sparkline(system: &System) -> Option<String> {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let sparkline: String = system
+        .cpus()
+        .iter()
+        .map(|cpu| {
+            let level = (cpu.cpu_usage().clamp(0.0, 100.0) / 100.0 * 7.0).round() as usize;
+            BLOCKS[level]
+        })
+        .collect();
+    (!sparkline.is_empty()).then_some(sparkline)
+}
+
+/// Count the cores busier than `threshold` percent, e.g. `3/12 (>50%)`.
+/// Relies on the same two-sample refresh as `sample_cpu_usage`.
+fn get_busy_cores(system: &System, threshold: f32) -> Option<String> {
+    let cpus = system.cpus();
+    if cpus.is_empty() {
+        return None;
+    }
+    let busy = cpus
+        .iter()
+        .filter(|cpu| cpu.cpu_usage() > threshold)
+        .count();
+    Some(format!("{}/{} (>{:.0}%)", busy, cpus.len(), threshold))
+}
+
+/// Show the 1-minute load average against the logical core count, e.g.
+/// `0.52 (4% of 12 cores)`. Returns `None` on Windows, which has no load
+/// average.
+fn get_load_normalized(system: &System, units: &UnitsConfig) -> Option<String> {
+    if cfg!(target_os = "windows") {
+        return None;
+    }
+    let cores = system.cpus().len();
+    if cores == 0 {
+        return None;
+    }
+    let load = System::load_average().one;
+    let plural = if cores == 1 { "" } else { "s" };
+    Some(format!(
+        "{} ({:.0}% of {} core{})",
+        units.decimal(load, 2),
+        load / cores as f64 * 100.0,
+        cores,
+        plural
+    ))
+}
+
 /// Determine a human-friendly OS label, with Linux distributions resolved via `/etc/os-release`.
-fn get_os_info() -> String {
+/// Returns `None` for an unrecognized OS or Linux distribution.
+fn get_os_info(detailed: bool) -> Option<String> {
     if cfg!(target_os = "linux") {
-        get_linux_distribution().unwrap_or("Linux (Unknown Distro)".to_string())
+        get_linux_distribution(detailed)
     } else if cfg!(target_os = "windows") {
-        "Windows".to_string()
+        Some("Windows".to_string())
     } else if cfg!(target_os = "macos") {
-        "macOS".to_string()
+        Some("macOS".to_string())
     } else {
-        "Unknown OS".to_string()
+        None
     }
 }
 
-/// Accept booleans or stringly booleans (e.g. "true") for convenience.
-fn bool_from_str_or_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct BoolVisitor;
+/// Run a command and return its trimmed stdout, or `None` if it couldn't be
+/// started, exited unsuccessfully, or printed nothing.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
 
-    impl<'de> Visitor<'de> for BoolVisitor {
-        type Value = bool;
+/// Run `command` through `sh -c` and return its trimmed stdout. Gives up after
+/// `timeout`, killing the shell, and returns `None` on a nonzero exit too.
+fn shell_output(command: &str, timeout: Duration) -> Option<String> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // Drain stdout on another thread so a chatty command can't fill the pipe
+    // and block before it exits.
+    let mut stdout = child.stdout.take()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = String::new();
+        let _ = stdout.read_to_string(&mut buffer);
+        let _ = sender.send(buffer);
+    });
+    // A timeout too long to add to the clock never expires.
+    let deadline = Instant::now().checked_add(timeout);
+    let status = loop {
+        if let Some(status) = child.try_wait().ok()? {
+            break status;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    if !status.success() {
+        return None;
+    }
+    // Background jobs started by the command may keep the pipe open.
+    let stdout = receiver
+        .recv_timeout(deadline.map_or(timeout, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        }))
+        .ok()?;
+    let stdout = stdout.trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a boolean or a boolean-like string")
+/// Describe how long ago something happened, using the largest whole unit.
+fn format_elapsed(seconds: u64) -> String {
+    let (amount, unit) = if seconds >= 86_400 {
+        (seconds / 86_400, "day")
+    } else if seconds >= 3_600 {
+        (seconds / 3_600, "hour")
+    } else if seconds >= 60 {
+        (seconds / 60, "minute")
+    } else {
+        return "just now".to_string();
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{} {}{} ago", amount, unit, plural)
+}
+
+/// Find the most recent resume from suspend/hibernation in the journal for the
+/// current boot. Returns `None` when journald isn't available or the machine
+/// hasn't slept since booting.
+fn get_last_resume() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let output = Command::new("journalctl")
+        .args([
+            "--boot",
+            "--dmesg",
+            "--no-pager",
+            "--output=short-unix",
+            "--lines=1",
+            "--grep=PM: (suspend|hibernation) exit",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Lines look like `1718000000.123456 host kernel: PM: suspend exit`.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let timestamp: f64 = stdout
+        .lines()
+        .last()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    let elapsed = now.as_secs_f64() - timestamp;
+    Some(format_elapsed(elapsed.max(0.0) as u64))
+}
+
+/// Share of swap space in use, or `None` when no swap is configured.
+fn get_swap_percent(system: &System) -> Option<f64> {
+    let total = system.total_swap();
+    (total > 0).then(|| system.used_swap() as f64 / total as f64 * 100.0)
+}
+
+/// Summarize swap as `used / total Gb (percent%)`.
+fn get_swap_usage(system: &System, units: &UnitsConfig) -> Option<String> {
+    let percent = get_swap_percent(system)?;
+    Some(format!(
+        "{} / {} {} ({:.0}%)",
+        units.decimal(units.gigabytes(system.used_swap()), 1),
+        units.decimal(units.gigabytes(system.total_swap()), 1),
+        units.memory_label,
+        percent
+    ))
+}
+
+/// Summarize `/sys/devices/system/cpu/vulnerabilities`, counting issues the
+/// kernel reports as vulnerable versus mitigated.
+fn get_cpu_mitigations() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let entries = fs::read_dir("/sys/devices/system/cpu/vulnerabilities").ok()?;
+    let mut vulnerable = 0;
+    let mut mitigated = 0;
+    for entry in entries.flatten() {
+        let status = fs::read_to_string(entry.path()).unwrap_or_default();
+        if status.starts_with("Vulnerable") {
+            vulnerable += 1;
+        } else if status.starts_with("Mitigation") {
+            mitigated += 1;
         }
+    }
+    Some(format!(
+        "{} vulnerable, {} mitigated",
+        vulnerable, mitigated
+    ))
+}
 
-        fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
-            Ok(value)
+/// Report allocated versus maximum file handles from `/proc/sys/fs/file-nr`.
+fn get_open_fds(grouping: NumberGrouping) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    // The file holds three counters: allocated, allocated-but-unused, and the max.
+    let content = fs::read_to_string("/proc/sys/fs/file-nr").ok()?;
+    let mut counters = content.split_whitespace();
+    let allocated = counters.next()?.parse().ok()?;
+    let max = counters.nth(1)?.parse().ok()?;
+    Some(format!(
+        "{} / {}",
+        format_number(allocated, grouping),
+        format_number(max, grouping)
+    ))
+}
+
+/// Ask power-profiles-daemon for the active profile (e.g. `balanced`).
+/// Returns `None` when the daemon or its CLI isn't available.
+fn get_power_profile() -> Option<String> {
+    command_output("powerprofilesctl", &["get"])
+}
+
+/// Read the kernel's swappiness tunable from `/proc/sys/vm/swappiness`.
+fn get_swappiness() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let content = fs::read_to_string("/proc/sys/vm/swappiness").ok()?;
+    Some(content.trim().to_string())
+}
+
+/// List the current mode of every connected display via `xrandr`, optionally
+/// with its refresh rate (`2560x1440 @ 165Hz`). Displays whose rate can't be
+/// determined are shown with their resolution only.
+fn get_resolution(include_refresh_rate: bool) -> Option<String> {
+    let output = Command::new("xrandr").arg("--current").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut displays = Vec::new();
+    // Mode lines are indented; the active one carries a `*` after its rate.
+    for line in stdout.lines().filter(|line| line.starts_with(' ')) {
+        let mut tokens = line.split_whitespace();
+        let Some(mode) = tokens.next() else {
+            continue;
+        };
+        let Some(rate) = tokens.find(|token| token.contains('*')) else {
+            continue;
+        };
+        let rate = rate.trim_end_matches(['*', '+']).parse::<f64>().ok();
+        match rate {
+            Some(rate) if include_refresh_rate => {
+                displays.push(format!("{} @ {:.0}Hz", mode, rate));
+            }
+            _ => displays.push(mode.to_string()),
         }
+    }
+    (!displays.is_empty()).then(|| displays.join(", "))
+}
 
-        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            value
-                .parse::<bool>()
-                .map_err(|_| E::custom(format!("invalid boolean string: {}", value)))
+/// Count distinct GPUs under `/sys/class/drm`. A GPU exposes both a `cardN`
+/// and a `renderDN` node, so nodes are de-duplicated by their backing device.
+fn get_gpu_count() -> Option<String> {
+    let entries = fs::read_dir("/sys/class/drm").ok()?;
+    let mut devices = HashSet::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // Skip connector entries such as `card0-HDMI-A-1`.
+        let is_node = name
+            .strip_prefix("card")
+            .or_else(|| name.strip_prefix("renderD"))
+            .is_some_and(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()));
+        if !is_node {
+            continue;
+        }
+        if let Ok(device) = fs::canonicalize(entry.path().join("device")) {
+            devices.insert(device);
         }
+    }
+    (!devices.is_empty()).then(|| devices.len().to_string())
+}
 
-        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            self.visit_str(&value)
+/// Map each mount point to its filesystem options from `/proc/mounts`. Later
+/// entries win so over-mounts report the options that are actually in effect.
+fn read_mount_options() -> HashMap<String, String> {
+    let content = fs::read_to_string("/proc/mounts").unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let mount_point = columns.nth(1)?;
+            let options = columns.nth(1)?;
+            // Whitespace in mount points is octal-escaped, e.g. `\040` for a space.
+            let mount_point = mount_point
+                .replace("\\040", " ")
+                .replace("\\011", "\t")
+                .replace("\\134", "\\");
+            Some((mount_point, options.to_string()))
+        })
+        .collect()
+}
+
+/// Report whether SSDs are trimmed, either periodically by `fstrim.timer`
+/// (`enabled (weekly)`) or continuously via the `discard` mount option.
+/// Returns `None` when neither systemd nor `/proc/mounts` gives an answer.
+fn get_trim_status() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    // `is-enabled` exits nonzero for `disabled`, so read stdout regardless.
+    let timer = Command::new("systemctl")
+        .args(["is-enabled", "fstrim.timer"])
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    if timer.as_deref() == Some("enabled") {
+        // Looks like `TimersCalendar={ OnCalendar=weekly ; next_elapse=... }`.
+        let schedule = command_output(
+            "systemctl",
+            &["show", "fstrim.timer", "--property=TimersCalendar"],
+        )
+        .and_then(|calendar| {
+            let (_, rest) = calendar.split_once("OnCalendar=")?;
+            let schedule = rest.split([' ', ';']).next()?;
+            (!schedule.is_empty()).then(|| schedule.to_string())
+        });
+        return Some(match schedule {
+            Some(schedule) => format!("enabled ({})", schedule),
+            None => "enabled".to_string(),
+        });
+    }
+    let continuous = read_mount_options()
+        .values()
+        .any(|options| options.split(',').any(|option| option == "discard"));
+    if continuous {
+        return Some("enabled (continuous)".to_string());
+    }
+    (timer.as_deref() == Some("disabled")).then(|| "disabled".to_string())
+}
+
+/// List the active IO scheduler of each block device, e.g.
+/// `nvme0n1 [none], sda [mq-deadline]`, from `/sys/block/*/queue/scheduler`.
+/// Virtual devices (loop, zram, device-mapper) are skipped.
+fn get_io_scheduler() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut devices: Vec<_> = fs::read_dir("/sys/block")
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            fs::canonicalize(entry.path())
+                .is_ok_and(|path| !path.starts_with("/sys/devices/virtual"))
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    devices.sort();
+    let schedulers: Vec<String> = devices
+        .iter()
+        .filter_map(|device| {
+            // The active scheduler is bracketed: `mq-deadline kyber [bfq] none`.
+            let path = Path::new("/sys/block").join(device).join("queue/scheduler");
+            let content = fs::read_to_string(path).ok()?;
+            let active = content
+                .split_whitespace()
+                .find(|name| name.starts_with('['))?;
+            Some(format!("{} {}", device, active))
+        })
+        .collect();
+    (!schedulers.is_empty()).then(|| schedulers.join(", "))
+}
+
+/// Name the drive holding the root filesystem, e.g. `Samsung SSD 980 PRO 1TB`,
+/// from `/sys/block/*/device/model` and `vendor`. Partitions are traced to
+/// their disk and device-mapper volumes (LVM, LUKS) to the first device
+/// beneath them. Returns `None` when the root device can't be resolved.
+fn get_storage_model() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+    // Later entries win, as with over-mounts in `read_mount_options`.
+    let source = mounts.lines().rev().find_map(|line| {
+        let mut columns = line.split_whitespace();
+        let source = columns.next()?;
+        (columns.next()? == "/").then_some(source)
+    })?;
+    let device = fs::canonicalize(source).ok()?;
+    let mut name = device.file_name()?.to_string_lossy().into_owned();
+    // Bounded so a malformed `slaves` chain can't loop forever.
+    for _ in 0..8 {
+        let block = Path::new("/sys/class/block").join(&name);
+        let slave = fs::read_dir(block.join("slaves"))
+            .ok()
+            .and_then(|mut entries| entries.next()?.ok());
+        if let Some(slave) = slave {
+            name = slave.file_name().to_string_lossy().into_owned();
+            continue;
+        }
+        if block.join("partition").exists() {
+            // A partition's sysfs directory sits inside its disk's.
+            let disk = fs::canonicalize(&block).ok()?;
+            name = disk.parent()?.file_name()?.to_string_lossy().into_owned();
         }
+        break;
     }
+    let read = |file: &str| {
+        fs::read_to_string(
+            Path::new("/sys/block")
+                .join(&name)
+                .join("device")
+                .join(file),
+        )
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    };
+    let model = read("model")?;
+    // SATA disks report the bus (`ATA`) rather than a maker as the vendor.
+    match read("vendor").filter(|vendor| vendor != "ATA" && !model.starts_with(vendor.as_str())) {
+        Some(vendor) => Some(format!("{} {}", vendor, model)),
+        None => Some(model),
+    }
+}
 
-    deserializer.deserialize_any(BoolVisitor)
+/// Describe every mounted disk as `(mount point, "used / total Gb (fs)", used
+/// bytes)`, ordered by `sort` or, when unset, in filesystem-enumeration order. With
+/// `show_mount_options`, the options from `/proc/mounts` are appended after the
+/// filesystem type when they can be matched.
+fn get_disks(
+    show_mount_options: bool,
+    sort: Option<DiskSort>,
+    units: &UnitsConfig,
+) -> Vec<(String, String, u64)> {
+    let mount_options = if show_mount_options && cfg!(target_os = "linux") {
+        read_mount_options()
+    } else {
+        HashMap::new()
+    };
+    let gib = |bytes: u64| bytes as f64 / 1024.0 / 1024.0 / 1024.0;
+    let disks = Disks::new_with_refreshed_list();
+    let mut disks: Vec<_> = disks.iter().filter(|disk| disk.total_space() > 0).collect();
+    let usage = |disk: &Disk| 1.0 - disk.available_space() as f64 / disk.total_space() as f64;
+    match sort {
+        Some(DiskSort::Name) => disks.sort_by(|a, b| a.mount_point().cmp(b.mount_point())),
+        Some(DiskSort::Size) => disks.sort_by_key(|disk| std::cmp::Reverse(disk.total_space())),
+        Some(DiskSort::Usage) => disks.sort_by(|a, b| usage(b).total_cmp(&usage(a))),
+        None => {}
+    }
+    disks
+        .into_iter()
+        .map(|disk| {
+            let mount_point = disk.mount_point().to_string_lossy().into_owned();
+            let used = disk.total_space() - disk.available_space();
+            let mut details = disk.file_system().to_string_lossy().into_owned();
+            if let Some(options) = mount_options.get(&mount_point) {
+                details = format!("{}, {}", details, options);
+            }
+            let value = format!(
+                "{} / {} Gb ({})",
+                units.decimal(gib(used), 1),
+                units.decimal(gib(disk.total_space()), 1),
+                details
+            );
+            (mount_point, value, used)
+        })
+        .collect()
 }
 
-/// User-configurable toggles under the `[Display]` heading.
-#[derive(Debug, Deserialize)]
-struct DisplayConfig {
-    #[serde(deserialize_with = "bool_from_str_or_bool")]
-    cpu_model: bool,
-    #[serde(deserialize_with = "bool_from_str_or_bool")]
-    os: bool,
-    #[serde(deserialize_with = "bool_from_str_or_bool")]
-    uptime: bool,
-    #[serde(deserialize_with = "bool_from_str_or_bool")]
-    ram: bool,
-    #[serde(deserialize_with = "bool_from_str_or_bool")]
-    battery: bool,
+/// Pick the active level (marked with `*`) from an amdgpu `pp_dpm_*` table,
+/// whose lines look like `1: 1905Mhz *`, and return it in MHz.
+fn read_active_dpm_clock(path: &Path) -> Option<u32> {
+    let content = fs::read_to_string(path).ok()?;
+    let line = content
+        .lines()
+        .find(|line| line.trim_end().ends_with('*'))?;
+    let clock = line.split_whitespace().nth(1)?;
+    clock
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .parse()
+        .ok()
 }
 
-/// Wrapper for the whole `.config.toml` file so we can honor the `[Display]` table.
-#[derive(Debug, Deserialize)]
-struct Config {
-    #[serde(rename = "Display")]
-    display: DisplayConfig,
+/// Report the primary GPU's current core and memory clocks. NVIDIA cards are
+/// queried through `nvidia-smi`; AMD cards through the amdgpu DPM tables.
+fn get_gpu_clock() -> Option<String> {
+    let nvidia = command_output(
+        "nvidia-smi",
+        &[
+            "--query-gpu=clocks.gr,clocks.mem",
+            "--format=csv,noheader,nounits",
+        ],
+    );
+    if let Some(stdout) = nvidia {
+        let mut clocks = stdout.lines().next()?.split(',').map(str::trim);
+        let core = clocks.next()?;
+        let memory = clocks.next()?;
+        return Some(format!("{} MHz core, {} MHz mem", core, memory));
+    }
+
+    let mut cards: Vec<_> = fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join("device/pp_dpm_sclk").exists())
+        .collect();
+    cards.sort();
+    let device = cards.first()?.join("device");
+    let core = read_active_dpm_clock(&device.join("pp_dpm_sclk"))?;
+    match read_active_dpm_clock(&device.join("pp_dpm_mclk")) {
+        Some(memory) => Some(format!("{} MHz core, {} MHz mem", core, memory)),
+        None => Some(format!("{} MHz core", core)),
+    }
 }
 
-/// Read and deserialize the TOML configuration file.
-fn load_config(path: &str) -> Result<Config, Box<dyn std::error::Error>> {
-    let content = fs::read_to_string(path)?;
-    Ok(toml::from_str(&content)?)
+/// Count the processes holding NVIDIA GPU memory and sum their usage, e.g.
+/// `2 (3.1 Gb)`, via `nvidia-smi`. Returns `None` without an NVIDIA driver or
+/// when no process is using the GPU.
+fn get_gpu_processes(units: &UnitsConfig) -> Option<String> {
+    let stdout = command_output(
+        "nvidia-smi",
+        &[
+            "--query-compute-apps=pid,used_memory",
+            "--format=csv,noheader,nounits",
+        ],
+    )?;
+    // Each line is `pid, used MiB`; the memory reads `[N/A]` on some drivers.
+    let mut count = 0;
+    let mut used_mib = 0u64;
+    for line in stdout.lines() {
+        let Some((_, memory)) = line.split_once(',') else {
+            continue;
+        };
+        count += 1;
+        used_mib += memory.trim().parse::<u64>().unwrap_or(0);
+    }
+    (count > 0).then(|| {
+        format!(
+            "{} ({} {})",
+            count,
+            units.decimal(units.gigabytes(used_mib * 1024 * 1024), 1),
+            units.memory_label
+        )
+    })
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = load_config("src/.config.toml")?;
+/// Report whether a firewall is filtering traffic and which tool manages it,
+/// e.g. `active (ufw)`. ufw and firewalld are asked first; otherwise a
+/// non-empty nftables ruleset counts as active. Returns `None` when no known
+/// tool answers, which includes running without the root access they need.
+fn get_firewall_status() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    // Prints `Status: active` or `Status: inactive` followed by the rules.
+    if let Some(ufw) = command_output("ufw", &["status"]) {
+        if let Some(status) = ufw.lines().find_map(|line| line.strip_prefix("Status:")) {
+            return Some(format!("{} (ufw)", status.trim()));
+        }
+    }
+    // `--state` prints `not running` and exits nonzero when stopped.
+    if let Ok(output) = Command::new("firewall-cmd").arg("--state").output() {
+        let state = String::from_utf8_lossy(&output.stdout);
+        match state.trim() {
+            "running" => return Some("active (firewalld)".to_string()),
+            "not running" => return Some("inactive (firewalld)".to_string()),
+            _ => {}
+        }
+    }
+    let output = Command::new("nft")
+        .args(["list", "ruleset"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let active = !String::from_utf8_lossy(&output.stdout).trim().is_empty();
+    Some(format!(
+        "{} (nftables)",
+        if active { "active" } else { "inactive" }
+    ))
+}
 
-    let mut system = System::new_all();
-    // Refresh system data
-    system.refresh_all();
+/// Report the primary GPU's negotiated PCIe link, e.g. `PCIe 4.0 x16`, from
+/// `current_link_speed` and `current_link_width` under `/sys/class/drm`.
+fn get_gpu_link() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut cards: Vec<_> = fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join("device/current_link_speed").exists())
+        .collect();
+    cards.sort();
+    let device = cards.first()?.join("device");
+    let read = |file: &str| {
+        fs::read_to_string(device.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    // Speeds look like `16.0 GT/s PCIe`; each generation doubles the rate.
+    let speed = read("current_link_speed")?;
+    let rate: f64 = speed.split_whitespace().next()?.parse().ok()?;
+    let generation = match rate {
+        rate if rate >= 64.0 => "6.0",
+        rate if rate >= 32.0 => "5.0",
+        rate if rate >= 16.0 => "4.0",
+        rate if rate >= 8.0 => "3.0",
+        rate if rate >= 5.0 => "2.0",
+        _ => "1.0",
+    };
+    let width = read("current_link_width")?;
+    Some(format!("PCIe {} x{}", generation, width))
+}
 
-    // Hardware snapshot
-    if config.display.cpu_model {
-        if let Some(cpu_info) = get_cpu_info(&system) {
-            println!("{}", cpu_info);
+/// List the kernel driver bound to each GPU, e.g. `i915, nvidia`, from the
+/// `device/driver` symlink of every `/sys/class/drm/cardN`.
+fn get_gpu_driver() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut cards: Vec<_> = fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Skip connector entries such as `card0-HDMI-A-1`.
+            name.strip_prefix("card")
+                .is_some_and(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|entry| entry.path())
+        .collect();
+    cards.sort();
+    let drivers: Vec<String> = cards
+        .iter()
+        .filter_map(|card| fs::read_link(card.join("device/driver")).ok())
+        .filter_map(|target| Some(target.file_name()?.to_string_lossy().into_owned()))
+        .collect();
+    (!drivers.is_empty()).then(|| drivers.join(", "))
+}
+
+/// Read the systemd/D-Bus machine id, optionally shortened to its first eight
+/// characters. Returns `None` when neither id file exists.
+fn get_machine_id(short: bool) -> Option<String> {
+    let id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())?;
+    let id = id.trim();
+    if id.is_empty() {
+        return None;
+    }
+    if short {
+        Some(id.chars().take(8).collect())
+    } else {
+        Some(id.to_string())
+    }
+}
+
+/// Describe retch's own scheduling, e.g. `SCHED_OTHER nice 0`, from
+/// `sched_getscheduler` and `getpriority`. The policy is Linux-only; other
+/// Unixes report just the niceness.
+#[cfg(unix)]
+fn get_self_scheduling() -> Option<String> {
+    // Asking about the calling process can't fail, so `-1` is a real niceness
+    // here rather than an error.
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+    #[cfg(target_os = "linux")]
+    {
+        let policy = match unsafe { libc::sched_getscheduler(0) } {
+            -1 => return Some(format!("nice {}", nice)),
+            libc::SCHED_OTHER => "SCHED_OTHER".to_string(),
+            libc::SCHED_FIFO => "SCHED_FIFO".to_string(),
+            libc::SCHED_RR => "SCHED_RR".to_string(),
+            libc::SCHED_BATCH => "SCHED_BATCH".to_string(),
+            libc::SCHED_IDLE => "SCHED_IDLE".to_string(),
+            libc::SCHED_DEADLINE => "SCHED_DEADLINE".to_string(),
+            other => format!("policy {}", other),
+        };
+        Some(format!("{} nice {}", policy, nice))
+    }
+    #[cfg(not(target_os = "linux"))]
+    Some(format!("nice {}", nice))
+}
+
+#[cfg(not(unix))]
+fn get_self_scheduling() -> Option<String> {
+    None
+}
+
+/// Read the loaded microcode revision (e.g. `0xf0`) from `/proc/cpuinfo`.
+fn get_microcode() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let content = fs::read_to_string("/proc/cpuinfo").ok()?;
+    content.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "microcode").then(|| value.trim().to_string())
+    })
+}
+
+/// List batteries of connected peripherals (controllers, mice, headsets) as
+/// `(name, charge)` pairs. The kernel marks these with `scope=Device` under
+/// `/sys/class/power_supply`, which the `battery` crate leaves out.
+fn get_peripheral_batteries() -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir("/sys/class/power_supply") else {
+        return Vec::new();
+    };
+    let read = |path: &Path, name: &str| {
+        fs::read_to_string(path.join(name))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    let mut batteries = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if read(&path, "type").as_deref() != Some("Battery")
+            || read(&path, "scope").as_deref() != Some("Device")
+        {
+            continue;
+        }
+        let name = read(&path, "model_name")
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| entry.file_name().to_string_lossy().into_owned());
+        // Some devices only report a coarse level such as `Normal` or `Low`.
+        let charge = match read(&path, "capacity") {
+            Some(capacity) => format!("{}%", capacity),
+            None => match read(&path, "capacity_level") {
+                Some(level) => level,
+                None => continue,
+            },
+        };
+        batteries.push((name, charge));
+    }
+    batteries.sort();
+    batteries
+}
+
+/// Count sandboxed apps from Flatpak and Snap, e.g. `Flatpaks: 23, Snaps: 7`.
+/// Managers that aren't installed are left out; `None` when neither is.
+fn get_sandbox_packages() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut counts = Vec::new();
+    // `command_output` treats an empty list as missing, so check the status here.
+    if let Ok(output) = Command::new("flatpak")
+        .args(["list", "--app", "--columns=application"])
+        .output()
+    {
+        if output.status.success() {
+            let apps = String::from_utf8_lossy(&output.stdout).lines().count();
+            counts.push(format!("Flatpaks: {}", apps));
+        }
+    }
+    // `snap list` prints a header line first, or nothing but a notice to
+    // stderr when no snaps are installed.
+    if let Ok(output) = Command::new("snap").arg("list").output() {
+        if output.status.success() {
+            let snaps = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .count()
+                .saturating_sub(1);
+            counts.push(format!("Snaps: {}", snaps));
         }
     }
+    (!counts.is_empty()).then(|| counts.join(", "))
+}
 
-    // Collect system-level facts before printing them together.
-    let mut report_lines = Vec::new();
+/// Name the running Wayland compositor by matching known process names.
+/// Returns `None` outside a Wayland session (X11 or headless).
+fn get_compositor(system: &System) -> Option<String> {
+    std::env::var_os("WAYLAND_DISPLAY")?;
+    const COMPOSITORS: &[(&str, &str)] = &[
+        ("sway", "sway"),
+        ("Hyprland", "Hyprland"),
+        ("kwin_wayland", "KWin"),
+        ("gnome-shell", "mutter"),
+        ("weston", "weston"),
+        ("river", "river"),
+        ("wayfire", "wayfire"),
+        ("labwc", "labwc"),
+        ("niri", "niri"),
+        ("cosmic-comp", "cosmic-comp"),
+        ("hikari", "hikari"),
+        ("dwl", "dwl"),
+        ("gamescope", "gamescope"),
+    ];
+    system.processes().values().find_map(|process| {
+        let name = process.name().to_string_lossy();
+        COMPOSITORS
+            .iter()
+            .find(|(process_name, _)| name == *process_name)
+            .map(|(_, compositor)| compositor.to_string())
+    })
+}
 
-    if config.display.os {
-        report_lines.push(format!("OS: {}", get_os_info()));
+/// Identify the running sound server, preferring `pactl info` because it also
+/// reports the version (`PipeWire 1.0.5`), then falling back to matching
+/// PipeWire, PulseAudio, or JACK processes. Returns `None` when none is running.
+fn get_audio_server(system: &System) -> Option<String> {
+    if let Some(info) = command_output("pactl", &["info"]) {
+        let value = |key: &str| {
+            info.lines()
+                .find_map(|line| line.strip_prefix(key))
+                .map(|value| value.trim().to_string())
+        };
+        // PipeWire's compatibility layer reports `PulseAudio (on PipeWire 1.0.5)`.
+        if let Some(name) = value("Server Name:") {
+            if let Some((_, version)) = name.split_once("on PipeWire ") {
+                return Some(format!("PipeWire {}", version.trim_end_matches(')')));
+            }
+            if name.eq_ignore_ascii_case("pulseaudio") {
+                return Some(match value("Server Version:") {
+                    Some(version) => format!("PulseAudio {}", version),
+                    None => "PulseAudio".to_string(),
+                });
+            }
+        }
     }
+    const SERVERS: &[(&str, &str)] = &[
+        ("pipewire", "PipeWire"),
+        ("pulseaudio", "PulseAudio"),
+        ("jackd", "JACK"),
+        ("jackdbus", "JACK"),
+    ];
+    // Check in priority order: PipeWire can run alongside a JACK shim.
+    SERVERS.iter().find_map(|(process_name, server)| {
+        system
+            .processes()
+            .values()
+            .any(|process| process.name() == *process_name)
+            .then(|| server.to_string())
+    })
+}
 
-    if config.display.uptime {
-        report_lines.push(format!("Uptime: {}", format_uptime()));
+/// Report the audio graph's sample rate and buffer size, e.g.
+/// `48000 Hz, 128 frames`, from PipeWire's `settings` metadata (a forced
+/// rate or quantum wins over the default) or JACK's `jack_samplerate` and
+/// `jack_bufsize`. Returns `None` when neither server answers.
+fn get_audio_params() -> Option<String> {
+    if let Some(settings) = command_output("pw-metadata", &["-n", "settings"]) {
+        // Lines look like `update: id:0 key:'clock.rate' value:'48000' type:''`.
+        let value = |key: &str| {
+            let marker = format!("key:'{}' value:'", key);
+            settings.lines().find_map(|line| {
+                let (_, rest) = line.split_once(&marker)?;
+                let (value, _) = rest.split_once('\'')?;
+                // Forced settings read `0` while nothing is forcing them.
+                value.parse::<u32>().ok().filter(|value| *value != 0)
+            })
+        };
+        let rate = value("clock.force-rate").or_else(|| value("clock.rate"));
+        let quantum = value("clock.force-quantum").or_else(|| value("clock.quantum"));
+        if let (Some(rate), Some(quantum)) = (rate, quantum) {
+            return Some(format!("{} Hz, {} frames", rate, quantum));
+        }
     }
+    let rate = command_output("jack_samplerate", &[])?;
+    // `jack_bufsize` prints `buffer size = 128`.
+    let bufsize = command_output("jack_bufsize", &[])?;
+    let frames = bufsize.split_whitespace().last()?;
+    Some(format!("{} Hz, {} frames", rate, frames))
+}
 
-    if config.display.ram {
-        report_lines.push(format!("Ram: {} Gb", get_total_memory_gb(&system)));
+/// Read the default sink's volume from PulseAudio (or PipeWire's pulse
+/// layer), e.g. `45%` or `45% (muted)`. Channels are averaged when they differ.
+/// Returns `None` when no audio server is reachable.
+fn get_volume() -> Option<String> {
+    // Looks like `Volume: front-left: 29491 /  45% / -20.81 dB,   front-right: ...`.
+    let volume = command_output("pactl", &["get-sink-volume", "@DEFAULT_SINK@"])?;
+    let levels: Vec<u32> = volume
+        .lines()
+        .next()?
+        .split('/')
+        .filter_map(|part| part.trim().strip_suffix('%')?.parse().ok())
+        .collect();
+    if levels.is_empty() {
+        return None;
     }
+    let level = levels.iter().sum::<u32>() / levels.len() as u32;
+    let muted = command_output("pactl", &["get-sink-mute", "@DEFAULT_SINK@"])
+        .is_some_and(|mute| mute.trim() == "Mute: yes");
+    Some(if muted {
+        format!("{}% (muted)", level)
+    } else {
+        format!("{}%", level)
+    })
+}
 
-    if config.display.battery {
-        report_lines.push(get_battery_info()?);
+/// Read the cumulative context-switch and interrupt counters from `/proc/stat`.
+fn read_cpu_activity_counters() -> Option<(u64, u64)> {
+    let content = fs::read_to_string("/proc/stat").ok()?;
+    let mut ctxt = None;
+    let mut intr = None;
+    for line in content.lines() {
+        let mut columns = line.split_whitespace();
+        match columns.next() {
+            Some("ctxt") => ctxt = columns.next()?.parse().ok(),
+            // The first number after `intr` is the total; per-IRQ counts follow.
+            Some("intr") => intr = columns.next()?.parse().ok(),
+            _ => {}
+        }
+    }
+    Some((ctxt?, intr?))
+}
+
+/// Abbreviate a per-second rate, e.g. `12k` or `1.2M`.
+fn format_rate(rate: f64, units: &UnitsConfig) -> String {
+    if rate >= 1_000_000.0 {
+        format!("{}M", units.decimal(rate / 1_000_000.0, 1))
+    } else if rate >= 1_000.0 {
+        format!("{:.0}k", rate / 1_000.0)
+    } else {
+        format!("{:.0}", rate)
     }
+}
 
-    if !report_lines.is_empty() {
-        println!("{}", report_lines.join("\n"));
+/// Sample context switches and interrupts twice, a short interval apart, and
+/// report their per-second rates.
+fn get_cpu_activity(units: &UnitsConfig) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
     }
+    const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+    let (ctxt_before, intr_before) = read_cpu_activity_counters()?;
+    thread::sleep(SAMPLE_INTERVAL);
+    let (ctxt_after, intr_after) = read_cpu_activity_counters()?;
+    let per_second = |before: u64, after: u64| {
+        after.saturating_sub(before) as f64 / SAMPLE_INTERVAL.as_secs_f64()
+    };
+    Some(format!(
+        "{} ctx switches/s, {} interrupts/s",
+        format_rate(per_second(ctxt_before, ctxt_after), units),
+        format_rate(per_second(intr_before, intr_after), units)
+    ))
+}
 
-    Ok(())
+/// Count installed fonts as listed by fontconfig's `fc-list`.
+/// Returns `None` when fontconfig isn't installed.
+fn get_font_count() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let fonts = command_output("fc-list", &["--format", "%{file}\\n"])?;
+    Some(fonts.lines().count().to_string())
+}
+
+/// Count established TCP connections over IPv4 and IPv6 from `/proc/net`.
+fn get_connection_count(grouping: NumberGrouping) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut established = 0u64;
+    let mut readable = false;
+    for path in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        readable = true;
+        // The fourth column is the socket state in hex; `01` is ESTABLISHED.
+        established += content
+            .lines()
+            .skip(1)
+            .filter(|line| line.split_whitespace().nth(3) == Some("01"))
+            .count() as u64;
+    }
+    readable.then(|| format!("{} established", format_number(established, grouping)))
+}
+
+/// Name of the interface carrying the IPv4 default route, from `/proc/net/route`.
+fn get_default_route_interface() -> Option<String> {
+    let content = fs::read_to_string("/proc/net/route").ok()?;
+    content.lines().skip(1).find_map(|line| {
+        let mut columns = line.split_whitespace();
+        let interface = columns.next()?;
+        (columns.next()? == "00000000").then(|| interface.to_string())
+    })
+}
+
+/// Report the machine's LAN IPv4 address. A `preferred` interface is used when
+/// it exists and has an IPv4 address; otherwise the default-route interface is
+/// chosen, falling back to the first non-loopback interface by name.
+fn get_local_ip(preferred: Option<&str>) -> Option<String> {
+    let networks = Networks::new_with_refreshed_list();
+    let ipv4_of = |name: &str| {
+        networks.get(name).and_then(|data| {
+            data.ip_networks()
+                .iter()
+                .find(|network| network.addr.is_ipv4() && !network.addr.is_loopback())
+                .map(|network| network.addr.to_string())
+        })
+    };
+    if let Some(address) = preferred.and_then(ipv4_of) {
+        return Some(address);
+    }
+    if let Some(address) = get_default_route_interface().and_then(|name| ipv4_of(&name)) {
+        return Some(address);
+    }
+    let mut names: Vec<&String> = networks.keys().collect();
+    names.sort();
+    names.into_iter().find_map(|name| ipv4_of(name))
+}
+
+/// Report the machine's primary global IPv6 address, looking at the
+/// default-route interface first and then every other interface by name.
+/// Loopback, `fe80::/10` link-local and `fc00::/7` unique-local addresses
+/// are skipped.
+fn get_ipv6_address() -> Option<String> {
+    let networks = Networks::new_with_refreshed_list();
+    let ipv6_of = |name: &str| {
+        networks.get(name).and_then(|data| {
+            data.ip_networks()
+                .iter()
+                .find_map(|network| match network.addr {
+                    IpAddr::V6(addr)
+                        if !addr.is_loopback()
+                            && !addr.is_unspecified()
+                            && addr.segments()[0] & 0xffc0 != 0xfe80
+                            && addr.segments()[0] & 0xfe00 != 0xfc00 =>
+                    {
+                        Some(addr.to_string())
+                    }
+                    _ => None,
+                })
+        })
+    };
+    if let Some(address) = get_default_route_interface().and_then(|name| ipv6_of(&name)) {
+        return Some(address);
+    }
+    let mut names: Vec<&String> = networks.keys().collect();
+    names.sort();
+    names.into_iter().find_map(|name| ipv6_of(name))
+}
+
+/// Sum receive and transmit errors over every non-loopback interface since
+/// boot, e.g. `rx 0 errors, 3 dropped; tx 0 errors, 12 dropped`. Dropped
+/// packets come from `/sys/class/net/*/statistics` and are left out where
+/// that isn't available. Returns `None` when there are no interfaces.
+fn get_network_errors() -> Option<String> {
+    let networks = Networks::new_with_refreshed_list();
+    let interfaces: Vec<_> = networks
+        .iter()
+        .filter(|(name, _)| name.as_str() != "lo")
+        .collect();
+    if interfaces.is_empty() {
+        return None;
+    }
+    let rx_errors: u64 = interfaces
+        .iter()
+        .map(|(_, data)| data.total_errors_on_received())
+        .sum();
+    let tx_errors: u64 = interfaces
+        .iter()
+        .map(|(_, data)| data.total_errors_on_transmitted())
+        .sum();
+    let dropped = |counter: &str| -> Option<u64> {
+        interfaces
+            .iter()
+            .map(|(name, _)| {
+                let path = Path::new("/sys/class/net")
+                    .join(name)
+                    .join("statistics")
+                    .join(counter);
+                fs::read_to_string(path).ok()?.trim().parse::<u64>().ok()
+            })
+            .sum()
+    };
+    match (dropped("rx_dropped"), dropped("tx_dropped")) {
+        (Some(rx_dropped), Some(tx_dropped)) => Some(format!(
+            "rx {} errors, {} dropped; tx {} errors, {} dropped",
+            rx_errors, rx_dropped, tx_errors, tx_dropped
+        )),
+        _ => Some(format!("rx {} errors; tx {} errors", rx_errors, tx_errors)),
+    }
+}
+
+/// Determine the desktop scale factor from the toolkit environment variables,
+/// falling back to the X resource DPI relative to 96. Returns `None` when no
+/// source reports a scale.
+fn get_display_scale(units: &UnitsConfig) -> Option<String> {
+    let from_env = ["GDK_SCALE", "QT_SCALE_FACTOR"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok()?.trim().parse::<f64>().ok());
+    let scale = from_env.or_else(|| {
+        let resources = command_output("xrdb", &["-query"])?;
+        resources.lines().find_map(|line| {
+            let dpi = line.strip_prefix("Xft.dpi:")?.trim().parse::<f64>().ok()?;
+            Some(dpi / 96.0)
+        })
+    })?;
+    if !scale.is_finite() || scale <= 0.0 {
+        return None;
+    }
+    // Show `2.0` and `1.25` rather than `2.00` or `1.3`.
+    let formatted = units.decimal(scale, 2);
+    let trimmed = formatted.trim_end_matches('0');
+    Some(if trimmed.ends_with(units.decimal_separator) {
+        format!("{}0", trimmed)
+    } else {
+        trimmed.to_string()
+    })
+}
+
+/// Report the deepest idle state (C-state) CPU 0 has entered since boot, from
+/// the cpuidle statistics. Returns `None` when cpuidle isn't available.
+fn get_cstate_info() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let entries = fs::read_dir("/sys/devices/system/cpu/cpu0/cpuidle").ok()?;
+    // States are numbered from shallowest (`state0`, usually POLL) to deepest.
+    let mut states: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let index = name.to_str()?.strip_prefix("state")?.parse().ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+    states.sort();
+    let read = |path: &Path, file: &str| {
+        fs::read_to_string(path.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    states.iter().rev().find_map(|(_, path)| {
+        let usage: u64 = read(path, "usage")?.parse().ok()?;
+        if usage == 0 {
+            return None;
+        }
+        Some(format!("{} (deepest)", read(path, "name")?))
+    })
+}
+
+/// Read the charge cap set for battery conservation mode from the first
+/// system battery's `charge_control_end_threshold`, when the driver exposes it.
+fn get_battery_charge_limit() -> Option<u8> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut batteries: Vec<PathBuf> = fs::read_dir("/sys/class/power_supply")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("BAT"))
+        })
+        .collect();
+    batteries.sort();
+    batteries.iter().find_map(|path| {
+        fs::read_to_string(path.join("charge_control_end_threshold"))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
+/// Report the wattage of the plugged-in power adapter, e.g. `65 W`, from
+/// `power_now` or, failing that, the advertised `voltage_max` × `current_max`
+/// of an online `Mains` or `USB` supply. Returns `None` on battery or desktops.
+fn get_adapter_watts() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut supplies: Vec<PathBuf> = fs::read_dir("/sys/class/power_supply")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    supplies.sort();
+    let read = |path: &Path, file: &str| {
+        fs::read_to_string(path.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    // Readings are in micro-units (µW, µV, µA).
+    let number = |path: &Path, file: &str| -> Option<f64> {
+        read(path, file)?.parse().ok().filter(|value| *value > 0.0)
+    };
+    supplies.iter().find_map(|path| {
+        let kind = read(path, "type")?;
+        if !matches!(kind.as_str(), "Mains" | "USB") || read(path, "online")?.as_str() != "1" {
+            return None;
+        }
+        let microwatts = number(path, "power_now").or_else(|| {
+            Some(number(path, "voltage_max")? * number(path, "current_max")? / 1_000_000.0)
+        })?;
+        Some(format!("{:.0} W", microwatts / 1_000_000.0))
+    })
+}
+
+/// Sum the sizes of all populated memory modules from the SMBIOS type 17
+/// (Memory Device) tables exposed under `/sys/firmware/dmi/entries`. These are
+/// usually only readable by root, so `None` is common for regular users.
+fn get_installed_memory_bytes() -> Option<u64> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut total = 0u64;
+    let mut found = false;
+    for entry in fs::read_dir("/sys/firmware/dmi/entries").ok()?.flatten() {
+        if !entry.file_name().to_string_lossy().starts_with("17-") {
+            continue;
+        }
+        let Ok(raw) = fs::read(entry.path().join("raw")) else {
+            continue;
+        };
+        let word = |offset: usize| {
+            raw.get(offset..offset + 2)
+                .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        };
+        let Some(size) = word(0x0C) else {
+            continue;
+        };
+        found = true;
+        let bytes = match size {
+            // Empty slot, or size unknown.
+            0 | 0xFFFF => 0,
+            // Modules of 32 GB and up store their size in MB at offset 0x1C.
+            0x7FFF => raw
+                .get(0x1C..0x20)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as u64 * 1024 * 1024)
+                .unwrap_or(0),
+            // Bit 15 selects KB rather than MB granularity.
+            size if size & 0x8000 != 0 => (size & 0x7FFF) as u64 * 1024,
+            size => size as u64 * 1024 * 1024,
+        };
+        total += bytes;
+    }
+    (found && total > 0).then_some(total)
+}
+
+/// Describe RAM as `15.5 Gb usable (16 Gb installed)`, or just the usable
+/// figure when the installed size can't be read from DMI. With `show_used`,
+/// used memory leads: `6.0/15.5 Gb usable (16 Gb installed)`.
+fn get_ram_installed_vs_usable(
+    system: &System,
+    units: &UnitsConfig,
+    show_used: bool,
+) -> Option<String> {
+    let usable = system.total_memory();
+    if usable == 0 {
+        return None;
+    }
+    let usable_gb = units.decimal(units.gigabytes(usable), 1);
+    let usable_text = if show_used {
+        format!(
+            "{}/{} {} usable",
+            units.decimal(units.gigabytes(system.used_memory()), 1),
+            usable_gb,
+            units.memory_label
+        )
+    } else {
+        format!("{} {} usable", usable_gb, units.memory_label)
+    };
+    Some(match get_installed_memory_bytes() {
+        Some(installed) => format!(
+            "{} ({:.0} {} installed)",
+            usable_text,
+            units.gigabytes(installed),
+            units.memory_label
+        ),
+        None => usable_text,
+    })
+}
+
+/// Compute the backlight level as a percentage of `max_brightness` for the
+/// first device under `/sys/class/backlight`. Returns `None` on machines
+/// without a backlight (desktops, external monitors).
+fn get_brightness() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut devices: Vec<PathBuf> = fs::read_dir("/sys/class/backlight")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    devices.sort();
+    let read = |path: &Path, file: &str| -> Option<f64> {
+        fs::read_to_string(path.join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    devices.iter().find_map(|device| {
+        let brightness = read(device, "brightness")?;
+        let max = read(device, "max_brightness").filter(|max| *max > 0.0)?;
+        Some(format!("{:.0}%", brightness / max * 100.0))
+    })
+}
+
+/// Map the SMBIOS chassis type code from
+/// `/sys/devices/virtual/dmi/id/chassis_type` to a readable label.
+fn get_chassis_type() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let code: u8 = fs::read_to_string("/sys/devices/virtual/dmi/id/chassis_type")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let label = match code {
+        3 | 4 | 6 | 7 | 13 | 15 | 16 | 35 => "Desktop",
+        5 => "Pizza Box",
+        8 => "Portable",
+        9 | 10 | 14 => "Laptop",
+        11 => "Handheld",
+        12 => "Docking Station",
+        17 | 23 | 28 => "Server",
+        18 => "Expansion Chassis",
+        19 => "Sub Chassis",
+        20 => "Bus Expansion Chassis",
+        21 => "Peripheral Chassis",
+        22 => "RAID Chassis",
+        24 => "Sealed-case PC",
+        25 => "Multi-system Chassis",
+        26 => "Compact PCI",
+        27 => "Advanced TCA",
+        29 => "Blade Enclosure",
+        30 => "Tablet",
+        31 => "Convertible",
+        32 => "Detachable",
+        33 => "IoT Gateway",
+        34 => "Embedded PC",
+        36 => "Stick PC",
+        // 1 is `Other` and 2 `Unknown`; neither says anything useful.
+        _ => return None,
+    };
+    Some(label.to_string())
+}
+
+/// Name the default web browser: the `.desktop` id from `xdg-settings` on
+/// Linux (`firefox`), or the `http` handler's ProgId from the registry on
+/// Windows (`ChromeHTML`). Returns `None` when no default is set.
+fn get_default_browser() -> Option<String> {
+    if cfg!(target_os = "windows") {
+        let output = command_output(
+            "reg",
+            &[
+                "query",
+                r"HKCU\Software\Microsoft\Windows\Shell\Associations\UrlAssociations\http\UserChoice",
+                "/v",
+                "ProgId",
+            ],
+        )?;
+        // The value line looks like `    ProgId    REG_SZ    ChromeHTML`.
+        return output.lines().find_map(|line| {
+            let mut columns = line.split_whitespace();
+            if columns.next()? != "ProgId" {
+                return None;
+            }
+            columns.nth(1).map(str::to_string)
+        });
+    }
+    let desktop = command_output("xdg-settings", &["get", "default-web-browser"])?;
+    let browser = desktop.strip_suffix(".desktop").unwrap_or(&desktop);
+    (!browser.is_empty()).then(|| browser.to_string())
+}
+
+/// Report the TPM's version, e.g. `2.0 present`, from
+/// `/sys/class/tpm/tpm0/tpm_version_major` on Linux or `tpmtool` on Windows.
+/// Without a TPM this is `not present` when `show_absent` is set, else `None`.
+fn get_tpm_info(show_absent: bool) -> Option<String> {
+    let absent = || show_absent.then(|| "not present".to_string());
+    if cfg!(target_os = "windows") {
+        let Some(info) = command_output("tpmtool", &["getdeviceinformation"]) else {
+            return absent();
+        };
+        // Lines look like `-TPM Present: True` and `-TPM Version: 2.0`.
+        let value = |key: &str| {
+            info.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name.trim_start_matches('-').trim() == key).then(|| value.trim().to_string())
+            })
+        };
+        if !value("TPM Present").is_some_and(|present| present.eq_ignore_ascii_case("true")) {
+            return absent();
+        }
+        return Some(match value("TPM Version") {
+            Some(version) => format!("{} present", version),
+            None => "present".to_string(),
+        });
+    }
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let device = Path::new("/sys/class/tpm/tpm0");
+    if !device.exists() {
+        return absent();
+    }
+    // Kernels before 5.6 don't expose the version.
+    Some(match fs::read_to_string(device.join("tpm_version_major")) {
+        Ok(major) => format!("{}.0 present", major.trim()),
+        Err(_) => "present".to_string(),
+    })
+}
+
+/// Describe the kernel's randomness sources: the active hardware RNG from
+/// `hw_random` (when one is selected) plus the kernel CRNG, e.g.
+/// `virtio_rng.0 + crng`. The CRNG is flagged as seeding while the entropy
+/// pool is still below the 256 bits it needs.
+fn get_rng_source() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let entropy: u32 = fs::read_to_string("/proc/sys/kernel/random/entropy_avail")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    let crng = if entropy >= 256 {
+        "crng"
+    } else {
+        "crng (seeding)"
+    };
+    let hardware = fs::read_to_string("/sys/devices/virtual/misc/hw_random/rng_current")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty() && name != "none");
+    Some(match hardware {
+        Some(hardware) => format!("{} + {}", hardware, crng),
+        None => crng.to_string(),
+    })
+}
+
+/// Name the hypervisor when running as a guest, from `/sys/hypervisor/type`
+/// (Xen) or the DMI vendor and product strings. Falls back to `Unknown` when
+/// the CPU's `hypervisor` flag is set but nothing names it; returns `None` on
+/// bare metal.
+fn get_hypervisor() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    if let Ok(kind) = fs::read_to_string("/sys/hypervisor/type") {
+        if kind.trim() == "xen" {
+            return Some("Xen".to_string());
+        }
+    }
+    let dmi = |file: &str| {
+        fs::read_to_string(Path::new("/sys/devices/virtual/dmi/id").join(file))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let identity = format!("{} {}", dmi("sys_vendor"), dmi("product_name"));
+    const HYPERVISORS: &[(&str, &str)] = &[
+        ("QEMU", "KVM/QEMU"),
+        ("KVM", "KVM"),
+        ("Microsoft Corporation Virtual Machine", "Microsoft Hyper-V"),
+        ("VMware", "VMware"),
+        ("VirtualBox", "VirtualBox"),
+        ("innotek", "VirtualBox"),
+        ("Parallels", "Parallels"),
+        ("Xen", "Xen"),
+        ("Amazon EC2", "Amazon EC2"),
+        ("Google Compute Engine", "Google Compute Engine"),
+        ("Bochs", "Bochs"),
+        ("BHYVE", "bhyve"),
+    ];
+    if let Some((_, name)) = HYPERVISORS
+        .iter()
+        .find(|(marker, _)| identity.contains(marker))
+    {
+        return Some(name.to_string());
+    }
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    let flagged = cpuinfo.lines().any(|line| {
+        line.split_once(':').is_some_and(|(key, value)| {
+            key.trim() == "flags" && value.split_whitespace().any(|flag| flag == "hypervisor")
+        })
+    });
+    flagged.then(|| "Unknown".to_string())
+}
+
+/// Name the container runtime this process runs under, plus the orchestrator
+/// when there is one, e.g. `containerd (kubernetes)`. Uses `/proc/1/cgroup`,
+/// marker files, `KUBERNETES_SERVICE_HOST`, and mounted runtime sockets.
+/// Returns `None` outside containers.
+fn get_container_runtime() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let cgroup = fs::read_to_string("/proc/1/cgroup").unwrap_or_default();
+    let kubernetes =
+        std::env::var_os("KUBERNETES_SERVICE_HOST").is_some() || cgroup.contains("kubepods");
+    // Checked in order: CRI runtimes also appear under `kubepods` paths.
+    const CGROUP_MARKERS: &[(&str, &str)] = &[
+        ("cri-containerd", "containerd"),
+        ("crio", "CRI-O"),
+        ("libpod", "Podman"),
+        ("docker", "Docker"),
+        ("containerd", "containerd"),
+        ("lxc", "LXC"),
+    ];
+    const MARKER_FILES: &[(&str, &str)] =
+        &[("/.dockerenv", "Docker"), ("/run/.containerenv", "Podman")];
+    const SOCKETS: &[(&str, &str)] = &[
+        ("/run/containerd/containerd.sock", "containerd"),
+        ("/var/run/crio/crio.sock", "CRI-O"),
+        ("/var/run/docker.sock", "Docker"),
+    ];
+    let runtime = CGROUP_MARKERS
+        .iter()
+        .find(|(marker, _)| cgroup.contains(marker))
+        .or_else(|| {
+            MARKER_FILES
+                .iter()
+                .find(|(path, _)| Path::new(path).exists())
+        });
+    // A runtime socket alone is just as likely on the host, so it only names
+    // the runtime once we know we're inside a pod.
+    let runtime = match runtime {
+        Some((_, name)) => name.to_string(),
+        None if kubernetes => SOCKETS
+            .iter()
+            .find(|(path, _)| Path::new(path).exists())
+            .map_or("Unknown", |(_, name)| name)
+            .to_string(),
+        None => return None,
+    };
+    if kubernetes {
+        Some(format!("{} (kubernetes)", runtime))
+    } else {
+        Some(runtime)
+    }
+}
+
+/// Send a plain HTTP/1.0 request to the link-local instance metadata service
+/// and return the body of a `200` response. Gives up quickly so non-cloud
+/// hosts aren't held up.
+fn metadata_request(method: &str, path: &str, headers: &[&str]) -> Option<String> {
+    use std::net::{SocketAddr, TcpStream};
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+    let address = SocketAddr::from(([169, 254, 169, 254], 80));
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(TIMEOUT)).ok()?;
+    let mut request = format!("{} {} HTTP/1.0\r\nHost: 169.254.169.254\r\n", method, path);
+    for header in headers {
+        request.push_str(header);
+        request.push_str("\r\n");
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    let (head, body) = response.split_once("\r\n\r\n")?;
+    let status = head.lines().next()?.split_whitespace().nth(1)?;
+    let body = body.trim();
+    (status == "200" && !body.is_empty()).then(|| body.to_string())
+}
+
+/// Ask the provider's instance metadata service for the instance type.
+fn get_cloud_instance_type(provider: &str) -> Option<String> {
+    match provider {
+        "AWS" => {
+            // IMDSv2 wants a session token first.
+            let token = metadata_request(
+                "PUT",
+                "/latest/api/token",
+                &["X-aws-ec2-metadata-token-ttl-seconds: 60"],
+            )?;
+            metadata_request(
+                "GET",
+                "/latest/meta-data/instance-type",
+                &[&format!("X-aws-ec2-metadata-token: {}", token)],
+            )
+        }
+        // Answers with `projects/<number>/machineTypes/<type>`.
+        "GCP" => metadata_request(
+            "GET",
+            "/computeMetadata/v1/instance/machine-type",
+            &["Metadata-Flavor: Google"],
+        )
+        .and_then(|path| path.rsplit('/').next().map(str::to_string)),
+        "Azure" => metadata_request(
+            "GET",
+            "/metadata/instance/compute/vmSize?api-version=2021-02-01&format=text",
+            &["Metadata: true"],
+        ),
+        _ => None,
+    }
+}
+
+/// Name the cloud provider from the DMI vendor strings, e.g. `AWS (t3.medium)`.
+/// EC2 Nitro instances put their type in the DMI product name; for others it
+/// is only looked up from the metadata service when `query_metadata` is set.
+/// Returns `None` on hosts that don't look like a cloud instance.
+fn get_cloud_provider(query_metadata: bool) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let dmi = |file: &str| {
+        fs::read_to_string(Path::new("/sys/devices/virtual/dmi/id").join(file))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let vendor = dmi("sys_vendor");
+    let product = dmi("product_name");
+    let asset_tag = dmi("chassis_asset_tag");
+    // Azure VMs share Hyper-V's vendor strings but carry a fixed asset tag.
+    const AZURE_ASSET_TAG: &str = "7783-7084-3265-9085-8269-3286-77";
+    let provider = if vendor == "Amazon EC2" || dmi("bios_vendor") == "Amazon EC2" {
+        "AWS"
+    } else if vendor == "Google" || product == "Google Compute Engine" {
+        "GCP"
+    } else if asset_tag == AZURE_ASSET_TAG {
+        "Azure"
+    } else if asset_tag == "OracleCloud.com" {
+        "Oracle Cloud"
+    } else if vendor == "DigitalOcean" {
+        "DigitalOcean"
+    } else if vendor == "Hetzner" {
+        "Hetzner"
+    } else {
+        return None;
+    };
+    let instance_type = if provider == "AWS" && product.contains('.') {
+        Some(product)
+    } else if query_metadata {
+        get_cloud_instance_type(provider)
+    } else {
+        None
+    };
+    Some(match instance_type {
+        Some(instance_type) => format!("{} ({})", provider, instance_type),
+        None => provider.to_string(),
+    })
+}
+
+/// List each ACPI thermal zone's temperature and its lowest trip point, e.g.
+/// `zone0 52°C (trip: 90°C passive)`. Zones without trip points show only
+/// their temperature.
+fn get_thermal_zones() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mut zones: Vec<(u32, PathBuf)> = fs::read_dir("/sys/class/thermal")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let index = name.to_str()?.strip_prefix("thermal_zone")?.parse().ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+    zones.sort();
+    let read = |path: &Path, file: &str| {
+        fs::read_to_string(path.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    // Temperatures are reported in millidegrees Celsius.
+    let celsius = |path: &Path, file: &str| -> Option<f64> {
+        Some(read(path, file)?.parse::<f64>().ok()? / 1000.0)
+    };
+    let descriptions: Vec<String> = zones
+        .iter()
+        .filter_map(|(index, path)| {
+            let temperature = celsius(path, "temp")?;
+            let lowest_trip = (0..)
+                .map_while(|trip| {
+                    let temp = celsius(path, &format!("trip_point_{}_temp", trip))?;
+                    let kind = read(path, &format!("trip_point_{}_type", trip)).unwrap_or_default();
+                    Some((temp, kind))
+                })
+                .filter(|(temp, _)| *temp > 0.0)
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            Some(match lowest_trip {
+                Some((trip, kind)) => format!(
+                    "zone{} {:.0}°C (trip: {:.0}°C {})",
+                    index, temperature, trip, kind
+                ),
+                None => format!("zone{} {:.0}°C", index, temperature),
+            })
+        })
+        .collect();
+    (!descriptions.is_empty()).then(|| descriptions.join(", "))
+}
+
+/// Read the kernel command line from `/proc/cmdline`.
+fn get_kernel_cmdline() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let cmdline = fs::read_to_string("/proc/cmdline").ok()?;
+    let cmdline = cmdline.trim();
+    (!cmdline.is_empty()).then(|| cmdline.to_string())
+}
+
+/// Accept booleans or stringly booleans (e.g. "true") for convenience.
+fn bool_from_str_or_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct BoolVisitor;
+
+    impl<'de> Visitor<'de> for BoolVisitor {
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a boolean or a boolean-like string")
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+            Ok(value)
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            value
+                .parse::<bool>()
+                .map_err(|_| E::custom(format!("invalid boolean string: {}", value)))
+        }
+
+        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            self.visit_str(&value)
+        }
+    }
+
+    deserializer.deserialize_any(BoolVisitor)
+}
+
+/// Short names accepted for fields wherever a field is named (`[Display]`,
+/// `[[sections]]`, `[HideWhen]`, `[RefreshInterval]`, `--qr`), so configs
+/// written for other fetch tools work unchanged.
+const FIELD_ALIASES: &[(&str, &str)] = &[
+    ("cpu", "cpu_model"),
+    ("processor", "cpu_model"),
+    ("mem", "ram"),
+    ("memory", "ram"),
+    ("bat", "battery"),
+];
+
+/// The canonical key for a field `name`, resolving `FIELD_ALIASES`.
+fn resolve_field_name(name: &str) -> &str {
+    FIELD_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, key)| key)
+}
+
+/// Rename aliased keys of a `[Display]` table to their canonical names,
+/// rejecting a key given under two names.
+fn canonical_display_table(table: toml::Table) -> Result<toml::Table, String> {
+    let mut canonical = toml::Table::new();
+    for (key, value) in table {
+        let resolved = resolve_field_name(&key).to_string();
+        if canonical.insert(resolved.clone(), value).is_some() {
+            return Err(format!(
+                "duplicate field `{}`, given under more than one name",
+                resolved
+            ));
+        }
+    }
+    Ok(canonical)
+}
+
+/// Deserialize `[Display]` after resolving aliases through `FIELD_ALIASES`.
+fn display_with_aliases<'de, D>(deserializer: D) -> Result<DisplayConfig, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let table = canonical_display_table(toml::Table::deserialize(deserializer)?)
+        .map_err(de::Error::custom)?;
+    DisplayConfig::deserialize(toml::Value::Table(table)).map_err(de::Error::custom)
+}
+
+/// User-configurable toggles under the `[Display]` heading.
+///
+/// Keys may also be given by their `FIELD_ALIASES`; unrecognized keys are
+/// rejected.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct DisplayConfig {
+    #[serde(deserialize_with = "bool_from_str_or_bool")]
+    cpu_model: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cpu_freq_round: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cpu_usage: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cpu_idle: bool,
+    #[serde(deserialize_with = "bool_from_str_or_bool")]
+    os: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    os_detailed: bool,
+    #[serde(deserialize_with = "bool_from_str_or_bool")]
+    uptime: bool,
+    #[serde(deserialize_with = "bool_from_str_or_bool")]
+    ram: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    ram_detailed_lines: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    ram_installed: bool,
+    /// Show used and usable memory on the `ram` line with the installed size
+    /// from DMI, e.g. `6.0/15.5 Gb usable (16 Gb installed)`.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    ram_show_installed: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    ram_baseline: bool,
+    #[serde(deserialize_with = "bool_from_str_or_bool")]
+    battery: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    threads: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    last_resume: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    swap: bool,
+    /// In `--watch` mode, mark whether swap use is rising or falling.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    swap_trend: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    mitigations: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    open_fds: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    power_profile: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    swappiness: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    resolution: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    refresh_rate: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    gpu_count: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    disks: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    disk_mount_opts: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    gpu_clock: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    machine_id: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    machine_id_short: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    microcode: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    battery_include_peripherals: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    compositor: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cpu_activity: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    fonts: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    local_ip: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    scale: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cstate: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    battery_limit: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    brightness: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cmdline: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    zombies: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    gpu_processes: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    trim: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    ipv6: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    firewall: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cpu_sparkline: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    chassis: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    browser: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    gpu_link: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    audio_server: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    temperature: bool,
+    /// In `--watch` mode, also show the hottest reading since start.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    temperature_peak: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cpu_clocks: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cores_active: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    rng_source: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    adapter_watts: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    volume: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    sandbox_packages: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    busy_cores: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    hypervisor: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    connections: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cloud: bool,
+    /// Let `cloud` ask the link-local metadata service for the instance type.
+    /// Off by default: it is a network request that can add latency.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cloud_metadata: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    thermal_zones: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    cache_domains: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    gpu_driver: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    container_runtime: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    self_sched: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    audio_params: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    network_errors: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    tpm: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    tpm_show_absent: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    time: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    load_normalized: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    io_scheduler: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    top_memory: bool,
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    storage_model: bool,
+}
+
+/// Thousands separator used when printing large whole numbers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum NumberGrouping {
+    /// `1048576`, matching the historical output.
+    #[default]
+    None,
+    /// `1,048,576`
+    Comma,
+    /// `1 048 576`
+    Space,
+}
+
+/// Every `[Display]` key that enables a field of its own, in report order.
+/// Sub-options that only adjust another field (e.g. `refresh_rate`) are left out.
+const FIELDS: &[&str] = &[
+    "cpu_model",
+    "cpu_usage",
+    "cpu_idle",
+    "os",
+    "uptime",
+    "ram",
+    "battery",
+    "threads",
+    "last_resume",
+    "swap",
+    "mitigations",
+    "open_fds",
+    "power_profile",
+    "swappiness",
+    "resolution",
+    "gpu_count",
+    "disks",
+    "gpu_clock",
+    "machine_id",
+    "microcode",
+    "compositor",
+    "cpu_activity",
+    "fonts",
+    "local_ip",
+    "scale",
+    "cstate",
+    "brightness",
+    "cmdline",
+    "zombies",
+    "gpu_processes",
+    "trim",
+    "ipv6",
+    "firewall",
+    "cpu_sparkline",
+    "chassis",
+    "browser",
+    "gpu_link",
+    "audio_server",
+    "temperature",
+    "cpu_clocks",
+    "cores_active",
+    "rng_source",
+    "adapter_watts",
+    "volume",
+    "sandbox_packages",
+    "busy_cores",
+    "hypervisor",
+    "connections",
+    "cloud",
+    "thermal_zones",
+    "cache_domains",
+    "gpu_driver",
+    "container_runtime",
+    "self_sched",
+    "audio_params",
+    "network_errors",
+    "tpm",
+    "time",
+    "load_normalized",
+    "io_scheduler",
+    "top_memory",
+    "storage_model",
+];
+
+/// Fields whose values identify the machine or its network, masked by `--anon`.
+/// The hostname in the `summary_header` banner is masked as well, and so is
+/// the username in disk mount points such as `/home/<user>`. `cmdline` can
+/// name disks by UUID.
+const SENSITIVE_FIELDS: &[&str] = &["machine_id", "local_ip", "ipv6", "cmdline"];
+
+/// Replacement shown for masked values.
+const ANON_MASK: &str = "***";
+
+impl DisplayConfig {
+    /// Whether any field in [`FIELDS`] is switched on; sub-options such as
+    /// `refresh_rate` don't count on their own.
+    fn any_enabled(&self) -> bool {
+        let Ok(toml::Value::Table(table)) = toml::Value::try_from(self) else {
+            return true;
+        };
+        FIELDS
+            .iter()
+            .any(|key| table.get(*key).and_then(toml::Value::as_bool) == Some(true))
+    }
+
+    /// A display config with every field in [`FIELDS`] switched on.
+    fn all() -> Self {
+        let table = FIELDS
+            .iter()
+            .map(|key| (key.to_string(), toml::Value::Boolean(true)))
+            .collect();
+        DisplayConfig::deserialize(toml::Value::Table(table))
+            .expect("FIELDS only lists valid display keys")
+    }
+
+    /// Enable just the field `key`, which must be listed in `FIELDS`.
+    fn only(key: &str) -> Self {
+        let table = FIELDS
+            .iter()
+            .map(|field| (field.to_string(), toml::Value::Boolean(*field == key)))
+            .collect();
+        DisplayConfig::deserialize(toml::Value::Table(table))
+            .expect("FIELDS only lists valid display keys")
+    }
+}
+
+/// How the uptime field is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum UptimeFormat {
+    /// `3d 4h 12m`, matching the historical output.
+    #[default]
+    Precise,
+    /// `~3 days`: only the largest whole unit.
+    Approximate,
+}
+
+/// How the battery field shows whether it is charging.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum BatteryStateStyle {
+    /// `62% (Discharging)`, matching the historical output.
+    #[default]
+    Word,
+    /// `62% ↓`
+    Symbol,
+    /// `62% ↓ (Discharging)`
+    Both,
+}
+
+/// Ordering for the per-disk lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DiskSort {
+    /// Mount point, alphabetically.
+    Name,
+    /// Largest capacity first.
+    Size,
+    /// Fullest (highest used percentage) first.
+    Usage,
+}
+
+/// A 24-bit color, written `#rrggbb` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+struct Rgb(u8, u8, u8);
+
+impl TryFrom<String> for Rgb {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("expected a color like `#303030`, got `{}`", value);
+        let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+        if hex.len() != 6 {
+            return Err(invalid());
+        }
+        let channel = |range| u8::from_str_radix(hex.get(range)?, 16).ok();
+        match (channel(0..2), channel(2..4), channel(4..6)) {
+            (Some(r), Some(g), Some(b)) => Ok(Rgb(r, g, b)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Rgb {
+    /// SGR parameters setting this color as the background: exact when
+    /// `COLORTERM` advertises truecolor, otherwise the nearest of the 16
+    /// standard colors (xterm's palette).
+    fn background_sgr(self) -> String {
+        let truecolor =
+            std::env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit");
+        if truecolor {
+            return format!("48;2;{};{};{}", self.0, self.1, self.2);
+        }
+        const PALETTE: [(u8, u8, u8); 16] = [
+            (0, 0, 0),
+            (205, 0, 0),
+            (0, 205, 0),
+            (205, 205, 0),
+            (0, 0, 238),
+            (205, 0, 205),
+            (0, 205, 205),
+            (229, 229, 229),
+            (127, 127, 127),
+            (255, 0, 0),
+            (0, 255, 0),
+            (255, 255, 0),
+            (92, 92, 255),
+            (255, 0, 255),
+            (0, 255, 255),
+            (255, 255, 255),
+        ];
+        let distance = |(r, g, b): (u8, u8, u8)| {
+            let delta = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+            delta(r, self.0) + delta(g, self.1) + delta(b, self.2)
+        };
+        let index = (0..PALETTE.len())
+            .min_by_key(|&index| distance(PALETTE[index]))
+            .unwrap_or(0);
+        // Codes 40-47 are the normal colors, 100-107 the bright ones.
+        let code = if index < 8 { 40 + index } else { 92 + index };
+        code.to_string()
+    }
+}
+
+/// Optional `[Theme]` table controlling colored output.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeConfig {
+    /// Color values that cross a warning threshold; off by default.
+    #[serde(deserialize_with = "bool_from_str_or_bool")]
+    colors: bool,
+    /// Dim unit suffixes (`GB`, `GHz`, `%`) and label separators so the
+    /// numbers stand out. Only applies when `colors` is on.
+    #[serde(deserialize_with = "bool_from_str_or_bool")]
+    dim_units: bool,
+    /// Swap usage percentage above which the swap field turns red.
+    swap_warning: f64,
+    /// RAM usage percentages that degrade the `--glyph` status.
+    memory_warning: f64,
+    memory_critical: f64,
+    /// Battery charge percentages below which `--glyph` degrades.
+    battery_warning: f64,
+    battery_critical: f64,
+    /// Hottest sensor readings (°C) that degrade the `--glyph` status.
+    temperature_warning: f64,
+    temperature_critical: f64,
+    /// Disk usage percentages, checked per mount point, that degrade the
+    /// `--glyph` status.
+    disk_warning: f64,
+    disk_critical: f64,
+    /// Background behind each label in text output, e.g. `"#303030"`. Only
+    /// applies when `colors` is on.
+    label_bg: Option<Rgb>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        ThemeConfig {
+            colors: false,
+            dim_units: false,
+            swap_warning: 50.0,
+            memory_warning: 80.0,
+            memory_critical: 95.0,
+            battery_warning: 20.0,
+            battery_critical: 10.0,
+            temperature_warning: 80.0,
+            temperature_critical: 95.0,
+            disk_warning: 90.0,
+            disk_critical: 97.0,
+            label_bg: None,
+        }
+    }
+}
+
+/// Optional `[Units]` table. The math and the label are separate so e.g.
+/// binary (1024-based) figures can still be labeled `GB`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct UnitsConfig {
+    /// Step between KB, MB, and GB for memory figures: `1024` or `1000`.
+    memory_base: u64,
+    /// Unit printed after memory figures, e.g. `Gb`, `GB`, or `GiB`.
+    memory_label: String,
+    /// Decimal separator for the top-level `locale`, set when the config is
+    /// loaded.
+    #[serde(skip)]
+    decimal_separator: char,
+}
+
+impl Default for UnitsConfig {
+    fn default() -> Self {
+        UnitsConfig {
+            memory_base: 1024,
+            memory_label: "Gb".to_string(),
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl UnitsConfig {
+    /// Convert bytes to (fractional) gigabytes using `memory_base`.
+    fn gigabytes(&self, bytes: u64) -> f64 {
+        bytes as f64 / (self.memory_base as f64).powi(3)
+    }
+
+    /// Write `value` with `precision` decimals and the locale's separator,
+    /// e.g. `15,5` for `de_DE`.
+    fn decimal(&self, value: f64, precision: usize) -> String {
+        let formatted = format!("{:.*}", precision, value);
+        if self.decimal_separator == '.' {
+            formatted
+        } else {
+            formatted.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+}
+
+/// Width that text output wraps values to: a column count, or `"auto"` for
+/// the terminal's width.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+enum MaxWidth {
+    Columns(usize),
+    Auto(AutoWidth),
+}
+
+/// The `"auto"` keyword of [`MaxWidth`].
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AutoWidth {
+    Auto,
+}
+
+impl MaxWidth {
+    /// The width in columns. `auto` tries `$COLUMNS`, then `stty size`, and
+    /// disables wrapping when neither is known (e.g. output is piped).
+    fn columns(self) -> Option<usize> {
+        match self {
+            MaxWidth::Columns(columns) => Some(columns),
+            MaxWidth::Auto(AutoWidth::Auto) => std::env::var("COLUMNS")
+                .ok()
+                .and_then(|columns| columns.trim().parse().ok())
+                .or_else(|| {
+                    if !io::stdout().is_terminal() {
+                        return None;
+                    }
+                    // Prints `rows columns` for the terminal on stdin.
+                    let output = Command::new("stty")
+                        .arg("size")
+                        .stdin(Stdio::inherit())
+                        .output()
+                        .ok()?;
+                    let size = String::from_utf8_lossy(&output.stdout);
+                    size.split_whitespace().nth(1)?.parse().ok()
+                })
+                .filter(|columns| *columns > 0),
+        }
+    }
+}
+
+/// Art chosen with `logo_override`: the name of a built-in logo, or the path
+/// of a text file whose lines are the logo.
+#[derive(Debug)]
+struct LogoOverride(Vec<String>);
+
+impl<'de> Deserialize<'de> for LogoOverride {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        if let Some((_, art)) = LOGOS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&value))
+        {
+            return Ok(LogoOverride(
+                art.iter().map(|line| line.to_string()).collect(),
+            ));
+        }
+        let art = fs::read_to_string(&value).map_err(|err| {
+            de::Error::custom(format!(
+                "logo_override `{}` is neither a built-in logo nor a readable file: {}",
+                value, err
+            ))
+        })?;
+        Ok(LogoOverride(
+            art.lines()
+                .map(|line| line.trim_end().to_string())
+                .collect(),
+        ))
+    }
+}
+
+/// Where the ASCII logo goes relative to the report in text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LogoPosition {
+    Left,
+    Right,
+    /// Above the report, separated by a blank line or the `logo_separator`.
+    Top,
+}
+
+/// A user-defined field from a `[[custom]]` table, whose value is the output
+/// of a shell command.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomField {
+    label: String,
+    /// Run with `sh -c`, so pipes and other shell syntax work.
+    command: String,
+    /// Seconds to wait for the command before showing the placeholder.
+    #[serde(default = "default_custom_timeout")]
+    timeout: f64,
+    /// Frames between runs of the command in `--watch` mode; every frame
+    /// when unset.
+    #[serde(default)]
+    refresh_interval: Option<u32>,
+}
+
+fn default_custom_timeout() -> f64 {
+    2.0
+}
+
+/// A user-defined field from a `[[file_field]]` table, showing the first line
+/// of a file such as a build tag or motd fragment.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileField {
+    label: String,
+    path: PathBuf,
+}
+
+/// Report keys shown under another field's `[Display]` switch, as
+/// (report key, parent key, sub-option that turns them on).
+const SUB_FIELDS: &[(&str, &str, &str)] = &[
+    ("ram_total", "ram", "ram_detailed_lines"),
+    ("ram_used", "ram", "ram_detailed_lines"),
+    ("ram_free", "ram", "ram_detailed_lines"),
+    (
+        "peripheral_batteries",
+        "battery",
+        "battery_include_peripherals",
+    ),
+];
+
+/// Report keys that have no `[Display]` switch of their own: user-defined
+/// fields and the `health_summary` verdict.
+const OTHER_REPORT_KEYS: &[&str] = &["custom", "file_field", "health_summary"];
+
+/// Report keys added by `--since` after the report is gathered, too late for
+/// `[HideWhen]`.
+const SINCE_KEYS: &[&str] = &["ram_change", "disk_change", "uptime_change"];
+
+/// Whether `key` names a field that can appear in the report.
+fn is_report_key(key: &str) -> bool {
+    is_hideable_key(key) || SINCE_KEYS.contains(&key)
+}
+
+/// Whether `[HideWhen]` can leave out the field `key`.
+fn is_hideable_key(key: &str) -> bool {
+    FIELDS.contains(&key)
+        || SUB_FIELDS.iter().any(|(sub, _, _)| *sub == key)
+        || OTHER_REPORT_KEYS.contains(&key)
+}
+
+/// The `[Display]` key a report key is shown under, e.g. `ram` for
+/// `ram_used`; other keys are their own.
+fn parent_field(key: &str) -> &str {
+    SUB_FIELDS
+        .iter()
+        .find(|(sub, _, _)| *sub == key)
+        .map_or(key, |(_, parent, _)| parent)
+}
+
+/// A titled group of fields from a `[[sections]]` table. `fields` names each
+/// field by its `[Display]` key (or alias), a sub-line key such as
+/// `ram_used`, or the label of a custom or file field. Naming a field brings
+/// its sub-lines along, e.g. `ram` includes the detailed RAM lines.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Section {
+    title: String,
+    fields: Vec<String>,
+}
+
+/// Turn on every `[Display]` key listed in `[[sections]]`, since sections
+/// describe the whole layout.
+fn enable_section_fields(config: &mut toml::Value) {
+    let listed: Vec<String> = config
+        .get("sections")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|section| section.get("fields")?.as_array())
+        .flatten()
+        .filter_map(|field| field.as_str())
+        .map(resolve_field_name)
+        .flat_map(|field| {
+            // A sub-line needs both its parent and the option showing it.
+            match SUB_FIELDS.iter().find(|(sub, _, _)| *sub == field) {
+                Some((_, parent, option)) => vec![*parent, *option],
+                None if FIELDS.contains(&field) => vec![field],
+                None => Vec::new(),
+            }
+        })
+        .map(str::to_string)
+        .collect();
+    if listed.is_empty() {
+        return;
+    }
+    let Some(table) = config.as_table_mut() else {
+        return;
+    };
+    let display = table
+        .entry("Display")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let Some(display) = display.as_table_mut() {
+        // Canonical names first, so `mem` in the file and `ram` here agree.
+        if let Ok(canonical) = canonical_display_table(display.clone()) {
+            *display = canonical;
+        }
+        for key in listed {
+            display.insert(key, toml::Value::Boolean(true));
+        }
+    }
+}
+
+/// Reorder the report into its `[[sections]]`, dropping fields no section
+/// lists, and record each section's title and field count for rendering.
+/// The `health_summary` verdict stays last unless a section places it.
+fn arrange_sections(report: &mut Report, sections: &[Section]) {
+    let mut remaining = std::mem::take(&mut report.fields);
+    for section in sections {
+        let start = report.fields.len();
+        for name in &section.fields {
+            // Instanced fields such as `disks`, and sub-lines such as
+            // `ram_used`, move over as a block.
+            let (matched, rest) = remaining.into_iter().partition(|field: &Field| {
+                field.key == name || parent_field(field.key) == name || field.label == *name
+            });
+            remaining = rest;
+            report.fields.extend(matched);
+        }
+        report
+            .sections
+            .push((section.title.clone(), report.fields.len() - start));
+    }
+    report.fields.extend(
+        remaining
+            .into_iter()
+            .filter(|field| field.key == "health_summary"),
+    );
+}
+
+/// Reject `[[sections]]` entries that name no field.
+fn validate_sections(config: &Config) -> Result<(), String> {
+    for section in &config.sections {
+        for name in &section.fields {
+            let known = is_report_key(name)
+                || config.custom.iter().any(|custom| custom.label == *name)
+                || config.file_field.iter().any(|file| file.label == *name);
+            if !known {
+                return Err(format!(
+                    "unknown field `{}` in section `{}`",
+                    name, section.title
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// First line of the file at `path`, trimmed, or `None` when it can't be read
+/// or is blank.
+fn read_first_line(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let line = content.lines().next()?.trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
+
+/// Condition under which a field is left out of the report, from the
+/// `[HideWhen]` table (e.g. `swap = "zero"`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "String")]
+enum HideWhen {
+    /// The reading is zero, e.g. no swap in use.
+    Zero,
+    /// The first percentage shown is 100%, e.g. a charged battery.
+    Full,
+    /// The value is blank or a placeholder for something undetected.
+    Empty,
+    /// The value is exactly this text.
+    Equals(String),
+}
+
+impl From<String> for HideWhen {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "zero" => HideWhen::Zero,
+            "full" => HideWhen::Full,
+            "empty" => HideWhen::Empty,
+            _ => HideWhen::Equals(value),
+        }
+    }
+}
+
+/// The first number in `value`, and whether a `%` directly follows it.
+fn leading_number(value: &str) -> Option<(f64, bool)> {
+    let start = value.find(|c: char| c.is_ascii_digit())?;
+    let rest = &value[start..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    let number = rest[..end].parse().ok()?;
+    Some((number, rest[end..].starts_with('%')))
+}
+
+impl HideWhen {
+    fn matches(&self, field: &Field) -> bool {
+        match self {
+            HideWhen::Zero => match &field.metric {
+                Some(metric) => metric.value == 0.0,
+                None => leading_number(&field.value).is_some_and(|(number, _)| number == 0.0),
+            },
+            HideWhen::Full => leading_number(&field.value)
+                .is_some_and(|(number, percent)| percent && number >= 100.0),
+            HideWhen::Empty => !field.detected || field.value.trim().is_empty(),
+            HideWhen::Equals(text) => field.value == *text,
+        }
+    }
+}
+
+/// Wrapper for the whole `.config.toml` file so we can honor the `[Display]` table.
+#[derive(Debug, Deserialize)]
+struct Config {
+    #[serde(default)]
+    output_format: OutputFormat,
+    #[serde(default)]
+    number_grouping: NumberGrouping,
+    /// Placeholder for fields that can't be detected; each field keeps its
+    /// historical wording (e.g. `Not detected`) when unset.
+    #[serde(default)]
+    unavailable_text: Option<String>,
+    /// Order of the per-disk lines; filesystem-enumeration order when unset.
+    #[serde(default)]
+    disk_sort: Option<DiskSort>,
+    /// Style of the uptime field; precise unless set to `approximate`.
+    #[serde(default)]
+    uptime_format: UptimeFormat,
+    /// Whether the battery state is a word, an arrow, or both.
+    #[serde(default)]
+    battery_state_style: BatteryStateStyle,
+    /// Interface whose address the `local_ip` field reports, e.g. `eth0`.
+    /// Auto-selected when unset or when the interface doesn't exist.
+    #[serde(default)]
+    local_ip_interface: Option<String>,
+    /// Longest value, in characters, shown in text output before it is cut
+    /// off with `…`. Unlimited when unset.
+    #[serde(default)]
+    max_value_width: Option<usize>,
+    /// Wrap long values at word boundaries to fit this many columns, or
+    /// `"auto"` for the terminal width. No wrapping when unset.
+    #[serde(default)]
+    max_width: Option<MaxWidth>,
+    /// Placement of the ASCII logo in text output; no logo when unset.
+    #[serde(default)]
+    logo_position: Option<LogoPosition>,
+    /// Rule drawn between a `top` logo and the report, this text repeated to
+    /// the report's width, e.g. `"─"`. A blank line when empty.
+    #[serde(default)]
+    logo_separator: String,
+    /// Built-in logo name or art file replacing the default logo.
+    #[serde(default)]
+    logo_override: Option<LogoOverride>,
+    /// Close the report with a `Status:` line grading the `[Theme]` thresholds.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    health_summary: bool,
+    /// Run slow, independent fields (commands, sampling delays) on background
+    /// threads instead of one after another.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    concurrent_gather: bool,
+    /// Start text output with a `=== host (up 14d 3h 2m) ===` banner line.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    summary_header: bool,
+    /// Draw a rounded box around the text report.
+    #[serde(default, deserialize_with = "bool_from_str_or_bool")]
+    border: bool,
+    /// Title set into the top edge of the `border`, e.g. the hostname.
+    #[serde(default)]
+    border_title: Option<String>,
+    /// Usage percentage above which a core counts towards `busy_cores`.
+    #[serde(default = "default_busy_threshold")]
+    busy_threshold: f32,
+    /// Locale for decimal numbers in field values: `C` (`15.5`), `system`, or
+    /// a locale name such as `de_DE` (`15,5`).
+    #[serde(default = "default_locale")]
+    locale: String,
+    /// Seconds between redraws in `--watch` mode.
+    #[serde(default = "default_watch_interval")]
+    watch_interval: f64,
+    /// CSV file each run appends its RAM, disk, and uptime figures to, read
+    /// back by `--since`. Nothing is logged when unset.
+    #[serde(default)]
+    log_file: Option<PathBuf>,
+    /// strftime-style format of the `time` field.
+    #[serde(default = "default_time_format")]
+    time_format: String,
+    #[serde(rename = "Display", deserialize_with = "display_with_aliases")]
+    display: DisplayConfig,
+    #[serde(rename = "Theme", default)]
+    theme: ThemeConfig,
+    #[serde(rename = "Units", default)]
+    units: UnitsConfig,
+    /// Per-field conditions, keyed by field, for leaving uninteresting
+    /// values out of the report.
+    #[serde(rename = "HideWhen", default)]
+    hide_when: HashMap<String, HideWhen>,
+    /// Frames between lookups of slow fields in `--watch` mode, keyed by
+    /// field, e.g. `sandbox_packages = 30`. Every frame when unset. Only
+    /// `BACKGROUND_FIELDS` and `custom.N` are cached, so only they are accepted.
+    #[serde(rename = "RefreshInterval", default)]
+    refresh_interval: HashMap<String, u32>,
+    /// Command-backed fields, shown after the built-in ones in file order.
+    #[serde(default)]
+    custom: Vec<CustomField>,
+    /// File-backed fields, shown after the command-backed ones in file order.
+    #[serde(default)]
+    file_field: Vec<FileField>,
+    /// Titled groups laying out the report; the flat `[Display]` order when
+    /// empty.
+    #[serde(default)]
+    sections: Vec<Section>,
+    /// Set by `--anon` rather than the config file.
+    #[serde(skip)]
+    anonymize: bool,
+}
+
+impl Config {
+    /// Whether anything at all is configured to appear in the report.
+    fn has_enabled_fields(&self) -> bool {
+        self.display.any_enabled()
+            || !self.custom.is_empty()
+            || !self.file_field.is_empty()
+            || self.health_summary
+            || self.summary_header
+    }
+
+    /// Lines of the logo: the `logo_override` art, or the built-in one.
+    fn logo(&self) -> Vec<&str> {
+        match &self.logo_override {
+            Some(LogoOverride(lines)) => lines.iter().map(String::as_str).collect(),
+            None => LOGO.to_vec(),
+        }
+    }
+
+    /// The user's placeholder for missing values, or `default` when unset.
+    fn unavailable_text<'a>(&'a self, default: &'a str) -> &'a str {
+        self.unavailable_text.as_deref().unwrap_or(default)
+    }
+}
+
+fn default_time_format() -> String {
+    "%Y-%m-%d %H:%M".to_string()
+}
+
+fn default_watch_interval() -> f64 {
+    2.0
+}
+
+fn default_locale() -> String {
+    "C".to_string()
+}
+
+fn default_busy_threshold() -> f32 {
+    50.0
+}
+
+/// Built-in configuration used when no config file exists, limited to fields
+/// every platform can report.
+const DEFAULT_CONFIG: &str = include_str!("default_config.toml");
+
+/// Write [`DEFAULT_CONFIG`] to `path`, leaving an existing file alone unless
+/// `force` is set. Returns whether the file was written.
+fn init_config(path: &str, force: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    let path = Path::new(path);
+    if path.exists() && !force {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, DEFAULT_CONFIG)?;
+    Ok(true)
+}
+
+/// Whether the config at `path` is JSON rather than TOML, going by extension.
+fn is_json_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Parse a JSON config into the `toml::Value` a TOML file would give, so both
+/// formats share the `Config` definition. `null` has no TOML equivalent:
+/// object members set to `null` are dropped (letting `#[serde(default)]`
+/// apply) and `null` inside arrays is rejected.
+fn parse_json_config(content: &str) -> Result<toml::Value, Box<dyn std::error::Error>> {
+    fn drop_nulls(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(members) => {
+                members.retain(|_, member| !member.is_null());
+                members.values_mut().for_each(drop_nulls);
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(drop_nulls),
+            _ => {}
+        }
+    }
+    let mut value: serde_json::Value = serde_json::from_str(content)?;
+    drop_nulls(&mut value);
+    Ok(toml::Value::try_from(value)?)
+}
+
+/// Read and deserialize the configuration file, picking JSON or TOML from the
+/// file extension. Both formats map onto the same `Config` definition. With
+/// `default_if_missing`, a missing file falls back to [`DEFAULT_CONFIG`].
+fn load_config(path: &str, default_if_missing: bool) -> Result<Config, Box<dyn std::error::Error>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if default_if_missing && err.kind() == io::ErrorKind::NotFound => {
+            return Ok(toml::from_str(DEFAULT_CONFIG)?);
+        }
+        Err(err) => return Err(format!("cannot read {}: {}", path, err).into()),
+    };
+    let is_json = is_json_path(path);
+    let mut config: Config = if is_json {
+        Config::deserialize(parse_json_config(&content)?)?
+    } else {
+        toml::from_str(&content)?
+    };
+    if !config.sections.is_empty() {
+        // Parse again with the listed fields switched on; the first pass
+        // reports errors against the file as written.
+        let mut value = if is_json {
+            parse_json_config(&content)?
+        } else {
+            toml::from_str(&content)?
+        };
+        enable_section_fields(&mut value);
+        config = Config::deserialize(value)?;
+    }
+    // Field names elsewhere in the file may be aliases too.
+    config.hide_when = std::mem::take(&mut config.hide_when)
+        .into_iter()
+        .map(|(key, condition)| (resolve_field_name(&key).to_string(), condition))
+        .collect();
+    config.refresh_interval = std::mem::take(&mut config.refresh_interval)
+        .into_iter()
+        .map(|(key, interval)| (resolve_field_name(&key).to_string(), interval))
+        .collect();
+    for section in &mut config.sections {
+        for name in &mut section.fields {
+            *name = resolve_field_name(name).to_string();
+        }
+    }
+    validate_sections(&config)?;
+    config.units.decimal_separator = decimal_separator(&config.locale);
+    // Sorted so the first bad key reported doesn't depend on hashing.
+    let mut hidden: Vec<&String> = config.hide_when.keys().collect();
+    hidden.sort();
+    if let Some(key) = hidden.into_iter().find(|key| !is_hideable_key(key)) {
+        if SINCE_KEYS.contains(&key.as_str()) {
+            return Err(format!("`{}` is added by --since and can't be hidden", key).into());
+        }
+        return Err(format!("unknown field `{}` in [HideWhen]", key).into());
+    }
+    if !config.watch_interval.is_finite()
+        || Duration::try_from_secs_f64(config.watch_interval.max(0.1)).is_err()
+    {
+        return Err(format!("invalid watch_interval {:?}", config.watch_interval).into());
+    }
+    for custom in &config.custom {
+        if !custom.timeout.is_finite()
+            || Duration::try_from_secs_f64(custom.timeout.max(0.0)).is_err()
+        {
+            return Err(format!(
+                "invalid timeout {:?} for custom field `{}`",
+                custom.timeout, custom.label
+            )
+            .into());
+        }
+    }
+    let mut intervals: Vec<&String> = config.refresh_interval.keys().collect();
+    intervals.sort();
+    let is_interval_key = |key: &str| match key.strip_prefix("custom.") {
+        Some(index) => index
+            .parse::<usize>()
+            .is_ok_and(|index| index < config.custom.len()),
+        None => BACKGROUND_FIELDS.contains(&key),
+    };
+    if let Some(key) = intervals.into_iter().find(|key| !is_interval_key(key)) {
+        if FIELDS.contains(&key.as_str()) {
+            return Err(format!(
+                "`{}` is read every frame, so [RefreshInterval] can't apply to it",
+                key
+            )
+            .into());
+        }
+        return Err(format!("unknown field `{}` in [RefreshInterval]", key).into());
+    }
+    if !matches!(config.units.memory_base, 1000 | 1024) {
+        return Err(format!(
+            "memory_base must be 1000 or 1024, got {}",
+            config.units.memory_base
+        )
+        .into());
+    }
+    // chrono only reports a bad specifier when the time is written out.
+    use std::fmt::Write as _;
+    let mut rendered = String::new();
+    if write!(
+        rendered,
+        "{}",
+        chrono::Local::now().format(&config.time_format)
+    )
+    .is_err()
+    {
+        return Err(format!("invalid time_format `{}`", config.time_format).into());
+    }
+    Ok(config)
+}
+
+/// Output flavour for the gathered report, chosen via `output_format`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OutputFormat {
+    /// `Label: value` lines for humans.
+    #[default]
+    Text,
+    /// Prometheus text exposition format, e.g. for node_exporter's textfile collector.
+    Prometheus,
+    /// A JSON object keyed by field; see [`Report`] for the shape.
+    Json,
+    /// An HTML `<table>` snippet for embedding in status pages.
+    Html,
+    /// The i3bar JSON protocol, so retch can stand in for i3status.
+    I3bar,
+    /// A TOML document with the same shape as `json`.
+    Toml,
+}
+
+/// Raw numeric reading behind a field whose displayed value is formatted for
+/// humans (e.g. `5 Gb` is exported as bytes).
+struct Metric {
+    name: &'static str,
+    help: &'static str,
+    value: f64,
+}
+
+/// ANSI SGR code for warning-level values.
+const ANSI_RED: &str = "31";
+
+/// Pick a warning color for a usage percentage: red once it exceeds `threshold`.
+fn percentage_color(percent: f64, threshold: f64) -> Option<&'static str> {
+    (percent > threshold).then_some(ANSI_RED)
+}
+
+/// ANSI SGR codes for the `--glyph` status levels.
+const ANSI_GREEN: &str = "32";
+const ANSI_YELLOW: &str = "33";
+
+/// Overall verdict across the threshold-backed readings, from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Health {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl Health {
+    fn glyph(self) -> &'static str {
+        match self {
+            Health::Ok => "✔",
+            Health::Warning => "!",
+            Health::Critical => "✘",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Health::Ok => ANSI_GREEN,
+            Health::Warning => ANSI_YELLOW,
+            Health::Critical => ANSI_RED,
+        }
+    }
+
+    /// Upper-case name used by the `health_summary` line.
+    fn label(self) -> &'static str {
+        match self {
+            Health::Ok => "OK",
+            Health::Warning => "WARNING",
+            Health::Critical => "CRITICAL",
+        }
+    }
+
+    /// Process exit code so scripts can branch without parsing the glyph.
+    fn exit_code(self) -> i32 {
+        match self {
+            Health::Ok => 0,
+            Health::Warning => 1,
+            Health::Critical => 2,
+        }
+    }
+}
+
+/// Grade a reading where higher values are worse (usage, temperature).
+fn grade_above(value: f64, warning: f64, critical: f64) -> Health {
+    if value >= critical {
+        Health::Critical
+    } else if value >= warning {
+        Health::Warning
+    } else {
+        Health::Ok
+    }
+}
+
+/// Combine memory usage, battery charge, the hottest sensor, and the fullest
+/// disk into a single verdict using the thresholds from `[Theme]`, naming the
+/// reading that graded worst (e.g. `disk / at 92%`). Missing readings count as OK.
+fn evaluate_health(
+    system: &System,
+    theme: &ThemeConfig,
+) -> Result<(Health, Option<String>), Box<dyn std::error::Error>> {
+    let mut health = Health::Ok;
+    let mut offender = None;
+    let mut consider = |grade: Health, describe: &dyn Fn() -> String| {
+        if grade > health {
+            health = grade;
+            offender = Some(describe());
+        }
+    };
+
+    let total_memory = system.total_memory();
+    if total_memory > 0 {
+        let memory_percent = system.used_memory() as f64 / total_memory as f64 * 100.0;
+        consider(
+            grade_above(memory_percent, theme.memory_warning, theme.memory_critical),
+            &|| format!("memory at {:.0}%", memory_percent),
+        );
+    }
+
+    if let Some(battery) = get_battery_info(BatteryStateStyle::default())? {
+        // Invert the scale so a lower charge grades worse.
+        let drained = 100.0 - battery.percentage as f64;
+        consider(
+            grade_above(
+                drained,
+                100.0 - theme.battery_warning,
+                100.0 - theme.battery_critical,
+            ),
+            &|| format!("battery at {:.0}%", battery.percentage),
+        );
+    }
+
+    if let Some(temperature) = get_max_temperature() {
+        consider(
+            grade_above(
+                temperature as f64,
+                theme.temperature_warning,
+                theme.temperature_critical,
+            ),
+            &|| format!("temperature at {:.0}°C", temperature),
+        );
+    }
+
+    for disk in Disks::new_with_refreshed_list().iter() {
+        if disk.total_space() == 0 {
+            continue;
+        }
+        let used_percent =
+            (1.0 - disk.available_space() as f64 / disk.total_space() as f64) * 100.0;
+        consider(
+            grade_above(used_percent, theme.disk_warning, theme.disk_critical),
+            &|| {
+                format!(
+                    "disk {} at {:.0}%",
+                    disk.mount_point().display(),
+                    used_percent
+                )
+            },
+        );
+    }
+
+    Ok((health, offender))
+}
+
+/// One gathered entry of the report.
+struct Field {
+    /// Config key that enables the field, also used as its machine-readable name.
+    key: &'static str,
+    label: String,
+    value: String,
+    /// Distinguishes repeated fields, e.g. the mount point of each disk.
+    instance: Option<String>,
+    metric: Option<Metric>,
+    /// ANSI SGR code applied to the value when colors are enabled.
+    color: Option<&'static str>,
+    /// False when `value` is only a placeholder such as `Not detected`.
+    detected: bool,
+}
+
+impl Field {
+    fn new(key: &'static str, label: impl Into<String>, value: String) -> Self {
+        Field {
+            key,
+            label: label.into(),
+            value,
+            instance: None,
+            metric: None,
+            color: None,
+            detected: true,
+        }
+    }
+
+    /// Mark the value as a placeholder for something that wasn't found.
+    fn undetected(mut self) -> Self {
+        self.detected = false;
+        self
+    }
+
+    fn with_instance(mut self, instance: String) -> Self {
+        self.instance = Some(instance);
+        self
+    }
+
+    fn with_metric(mut self, name: &'static str, help: &'static str, value: f64) -> Self {
+        self.metric = Some(Metric { name, help, value });
+        self
+    }
+
+    fn with_color(mut self, color: Option<&'static str>) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Label shown to humans, e.g. `Disk (/)` for an instanced field.
+    fn display_label(&self) -> String {
+        match &self.instance {
+            Some(instance) => format!("{} ({})", self.label, instance),
+            None => self.label.clone(),
+        }
+    }
+}
+
+/// Every enabled field, in display order, ready to be rendered in any format.
+///
+/// Serializes (for `json` output) to an object keyed by each field's
+/// `[Display]` config key in snake_case, e.g. `{"cpu_model": "...", "ram": "5 Gb"}`.
+/// Values are the strings shown in text output. Besides the `[Display]` keys,
+/// the object can hold `ram_total`, `ram_used` and `ram_free` (with
+/// `ram_detailed_lines`), `health_summary`, and `ram_change`, `disk_change`
+/// and `uptime_change` (with `--since`). Fields with one entry per instance
+/// (`disks`, `peripheral_batteries`) are always arrays of
+/// `{"name": ..., "value": ...}` objects, as are `custom` and `file_field`,
+/// named by label.
+/// Disabled or undetected fields are omitted rather than set to `null`.
+#[derive(Default)]
+struct Report {
+    fields: Vec<Field>,
+    /// Banner line printed above the fields in text output only.
+    header: Option<String>,
+    /// Title and field count of each `[[sections]]` group, in order, for text
+    /// output. Empty without sections.
+    sections: Vec<(String, usize)>,
+}
+
+impl Report {
+    fn push(&mut self, field: Field) {
+        self.fields.push(field);
+    }
+}
+
+/// One entry of an instanced field in serialized output.
+#[derive(Serialize)]
+struct InstanceEntry<'a> {
+    name: &'a str,
+    value: &'a str,
+}
+
+impl Serialize for Report {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        let mut seen = HashSet::new();
+        for field in self.fields.iter().filter(|field| field.detected) {
+            if !seen.insert(field.key) {
+                continue;
+            }
+            if field.instance.is_some() || matches!(field.key, "custom" | "file_field") {
+                let entries: Vec<InstanceEntry> = self
+                    .fields
+                    .iter()
+                    .filter(|other| other.key == field.key && other.detected)
+                    .map(|other| InstanceEntry {
+                        name: other.instance.as_deref().unwrap_or(&other.label),
+                        value: &other.value,
+                    })
+                    .collect();
+                map.serialize_entry(field.key, &entries)?;
+            } else {
+                map.serialize_entry(field.key, &field.value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Lookups started on background threads by `concurrent_gather`, keyed by
+/// field. Each result is collected where its field sits in the report, so the
+/// output order doesn't depend on which thread finishes first.
+#[derive(Default)]
+struct Background {
+    pending: HashMap<String, mpsc::Receiver<Option<String>>>,
+    /// Values from earlier `--watch` frames that aren't due for a refresh.
+    reuse: HashMap<String, Option<String>>,
+    /// Values looked up this time, for the next frame's `FieldCache`.
+    looked_up: HashMap<String, Option<String>>,
+}
+
+impl Background {
+    fn spawn(&mut self, key: String, lookup: impl FnOnce() -> Option<String> + Send + 'static) {
+        debug_assert!(key.starts_with("custom.") || BACKGROUND_FIELDS.contains(&key.as_str()));
+        if self.reuse.contains_key(&key) {
+            return;
+        }
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(lookup());
+        });
+        self.pending.insert(key, receiver);
+    }
+
+    /// Wait for the background result for `key`, or run `lookup` now when
+    /// nothing was started for it. A reusable cached value skips both.
+    fn take(&mut self, key: &str, lookup: impl FnOnce() -> Option<String>) -> Option<String> {
+        if let Some(value) = self.reuse.get(key) {
+            return value.clone();
+        }
+        let value = match self.pending.remove(key) {
+            Some(receiver) => receiver.recv().ok().flatten(),
+            None => lookup(),
+        };
+        self.looked_up.insert(key.to_string(), value.clone());
+        value
+    }
+}
+
+/// Slow field values kept across `--watch` frames, so fields with a
+/// `refresh_interval` are only looked up every that many frames.
+#[derive(Default)]
+struct FieldCache {
+    /// Frames gathered so far.
+    frame: u64,
+    /// Each field's last value and the frame it was looked up in.
+    values: HashMap<String, (u64, Option<String>)>,
+}
+
+impl FieldCache {
+    /// Cached values still within their field's refresh interval.
+    fn reusable(&self, config: &Config) -> HashMap<String, Option<String>> {
+        self.values
+            .iter()
+            .filter(|(key, (frame, _))| self.frame - frame < refresh_interval(config, key))
+            .map(|(key, (_, value))| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Record the values looked up this frame and move on to the next.
+    fn store(&mut self, looked_up: HashMap<String, Option<String>>) {
+        for (key, value) in looked_up {
+            self.values.insert(key, (self.frame, value));
+        }
+        self.frame += 1;
+    }
+}
+
+/// Frames between lookups of the field tracked as `key`: the custom field's
+/// own `refresh_interval`, falling back to the `[RefreshInterval]` entry.
+fn refresh_interval(config: &Config, key: &str) -> u64 {
+    let own = key
+        .strip_prefix("custom.")
+        .and_then(|index| index.parse::<usize>().ok())
+        .and_then(|index| config.custom.get(index))
+        .and_then(|custom| custom.refresh_interval);
+    let interval = own.or_else(|| config.refresh_interval.get(key).copied());
+    interval.unwrap_or(1).max(1).into()
+}
+
+/// Key under which the `index`th `[[custom]]` field's command is tracked.
+fn custom_key(index: usize) -> String {
+    format!("custom.{}", index)
+}
+
+/// How long to wait for a custom field's command before giving up.
+fn custom_timeout(custom: &CustomField) -> Duration {
+    Duration::from_secs_f64(custom.timeout.max(0.0))
+}
+
+/// A field lookup that needs nothing from the caller.
+type Lookup = fn() -> Option<String>;
+
+/// Fields looked up on background threads by `start_background_lookups`, the
+/// only built-ins whose values `[RefreshInterval]` can keep across frames.
+const BACKGROUND_FIELDS: &[&str] = &[
+    "last_resume",
+    "power_profile",
+    "gpu_clock",
+    "cpu_activity",
+    "fonts",
+    "scale",
+    "trim",
+    "firewall",
+    "browser",
+    "volume",
+    "sandbox_packages",
+    "audio_params",
+    "resolution",
+    "cloud",
+    "gpu_processes",
+];
+
+/// Start every enabled field that shells out, sleeps, or otherwise waits on
+/// something other than the shared `System`. Fields reading `System` stay on
+/// the calling thread.
+fn start_background_lookups(config: &Config, background: &mut Background) {
+    let display = &config.display;
+    let lookups: [(bool, &str, Lookup); 10] = [
+        (display.last_resume, "last_resume", get_last_resume),
+        (display.power_profile, "power_profile", get_power_profile),
+        (display.gpu_clock, "gpu_clock", get_gpu_clock),
+        (display.fonts, "fonts", get_font_count),
+        (display.trim, "trim", get_trim_status),
+        (display.firewall, "firewall", get_firewall_status),
+        (display.browser, "browser", get_default_browser),
+        (display.volume, "volume", get_volume),
+        (
+            display.sandbox_packages,
+            "sandbox_packages",
+            get_sandbox_packages,
+        ),
+        (display.audio_params, "audio_params", get_audio_params),
+    ];
+    for (enabled, key, lookup) in lookups {
+        if enabled {
+            background.spawn(key.to_string(), lookup);
+        }
+    }
+    if display.resolution {
+        let refresh_rate = display.refresh_rate;
+        background.spawn("resolution".to_string(), move || {
+            get_resolution(refresh_rate)
+        });
+    }
+    if display.cloud {
+        let metadata = display.cloud_metadata;
+        background.spawn("cloud".to_string(), move || get_cloud_provider(metadata));
+    }
+    if display.cpu_activity {
+        let units = config.units.clone();
+        background.spawn("cpu_activity".to_string(), move || get_cpu_activity(&units));
+    }
+    if display.scale {
+        let units = config.units.clone();
+        background.spawn("scale".to_string(), move || get_display_scale(&units));
+    }
+    if display.gpu_processes {
+        let units = config.units.clone();
+        background.spawn("gpu_processes".to_string(), move || {
+            get_gpu_processes(&units)
+        });
+    }
+    for (index, custom) in config.custom.iter().enumerate() {
+        let command = custom.command.clone();
+        let timeout = custom_timeout(custom);
+        background.spawn(custom_key(index), move || shell_output(&command, timeout));
+    }
+}
+
+/// Run the gather function of every enabled field.
+fn gather_report(
+    config: &Config,
+    system: &mut System,
+) -> Result<Report, Box<dyn std::error::Error>> {
+    gather_report_cached(config, system, &mut FieldCache::default())
+}
+
+/// Like `gather_report`, but reuse slow field values from `cache` until their
+/// `refresh_interval` has passed.
+fn gather_report_cached(
+    config: &Config,
+    system: &mut System,
+    cache: &mut FieldCache,
+) -> Result<Report, Box<dyn std::error::Error>> {
+    let mut report = Report::default();
+    if config.summary_header {
+        let host = if config.anonymize {
+            ANON_MASK.to_string()
+        } else {
+            System::host_name().unwrap_or_else(|| "localhost".to_string())
+        };
+        report.header = Some(format!(
+            "=== {} (up {}) ===",
+            host,
+            format_uptime(config.uptime_format)
+        ));
+    }
+    let mut background = Background {
+        reuse: cache.reusable(config),
+        ..Background::default()
+    };
+    if config.concurrent_gather {
+        start_background_lookups(config, &mut background);
+    }
+
+    // The process table is comparatively slow to load, so only refresh it
+    // when an enabled field inspects processes.
+    if config.display.threads
+        || config.display.compositor
+        || config.display.zombies
+        || config.display.audio_server
+        || config.display.top_memory
+    {
+        system.refresh_processes(ProcessesToUpdate::All, true);
+    }
+
+    // Hardware snapshot
+    if config.display.cpu_model {
+        if let Some(cpu_info) = get_cpu_info(system, config.display.cpu_freq_round, &config.units) {
+            report.push(Field::new("cpu_model", "CPU Model", cpu_info));
+        }
+    }
+
+    // Usage, idle, the per-core sparkline, and busy cores are views of the
+    // same measurement, so sample once.
+    let cpu_usage = (config.display.cpu_usage
+        || config.display.cpu_idle
+        || config.display.cpu_sparkline
+        || config.display.busy_cores)
+        .then(|| sample_cpu_usage(system));
+
+    if let Some(usage) = cpu_usage.filter(|_| config.display.cpu_usage) {
+        report.push(
+            Field::new("cpu_usage", "CPU Usage", format!("{:.0}%", usage)).with_metric(
+                "cpu_usage_percent",
+                "Global CPU usage in percent.",
+                usage as f64,
+            ),
+        );
+    }
+
+    if let Some(usage) = cpu_usage.filter(|_| config.display.cpu_idle) {
+        let idle = (100.0 - usage).max(0.0);
+        report.push(
+            Field::new("cpu_idle", "CPU Idle", format!("{:.0}%", idle)).with_metric(
+                "cpu_idle_percent",
+                "Global CPU idle time in percent.",
+                idle as f64,
+            ),
+        );
+    }
+
+    if config.display.cpu_sparkline {
+        if let Some(sparkline) = get_cpu_sparkline(system) {
+            report.push(Field::new("cpu_sparkline", "CPU", sparkline));
+        }
+    }
+
+    if config.display.busy_cores {
+        if let Some(busy) = get_busy_cores(system, config.busy_threshold) {
+            report.push(Field::new("busy_cores", "Busy Cores", busy));
+        }
+    }
+
+    if config.display.os {
+        let field = match get_os_info(config.display.os_detailed) {
+            Some(os) => Field::new("os", "OS", os),
+            None => {
+                let default = if cfg!(target_os = "linux") {
+                    "Linux (Unknown Distro)"
+                } else {
+                    "Unknown OS"
+                };
+                Field::new("os", "OS", config.unavailable_text(default).to_string()).undetected()
+            }
+        };
+        report.push(field);
+    }
+
+    if config.display.uptime {
+        report.push(
+            Field::new("uptime", "Uptime", format_uptime(config.uptime_format)).with_metric(
+                "uptime_seconds",
+                "Time since boot in seconds.",
+                System::uptime() as f64,
+            ),
+        );
+    }
+
+    let ram_baseline = (config.display.ram && config.display.ram_baseline)
+        .then(read_mem_baseline)
+        .flatten();
+    if config.display.ram && config.display.ram_detailed_lines {
+        // Total, used, and free each get their own line from the same refresh.
+        let units = &config.units;
+        let gib = |bytes: u64| {
+            format!(
+                "{} {}",
+                units.decimal(units.gigabytes(bytes), 1),
+                units.memory_label
+            )
+        };
+        let lines = [
+            (
+                "ram_total",
+                "RAM Total",
+                system.total_memory(),
+                "ram_bytes",
+                "Total physical memory in bytes.",
+            ),
+            (
+                "ram_used",
+                "RAM Used",
+                system.used_memory(),
+                "ram_used_bytes",
+                "Physical memory in use in bytes.",
+            ),
+            (
+                "ram_free",
+                "RAM Free",
+                system.available_memory(),
+                "ram_free_bytes",
+                "Physical memory available for new allocations in bytes.",
+            ),
+        ];
+        for (key, label, bytes, metric, help) in lines {
+            report.push(Field::new(key, label, gib(bytes)).with_metric(metric, help, bytes as f64));
+        }
+    } else if config.display.ram
+        && (config.display.ram_installed || config.display.ram_show_installed)
+    {
+        if let Some(ram) =
+            get_ram_installed_vs_usable(system, &config.units, config.display.ram_show_installed)
+        {
+            report.push(Field::new("ram", "Ram", ram).with_metric(
+                "ram_bytes",
+                "Total physical memory in bytes.",
+                system.total_memory() as f64,
+            ));
+        }
+    } else if let Some(baseline) = ram_baseline {
+        // Used memory, with its growth since `--mem-baseline save`.
+        let units = &config.units;
+        let used = system.used_memory();
+        let sign = if used < baseline { "-" } else { "+" };
+        report.push(
+            Field::new(
+                "ram",
+                "Ram",
+                format!(
+                    "{} {} ({}{} {} since baseline)",
+                    units.decimal(units.gigabytes(used), 1),
+                    units.memory_label,
+                    sign,
+                    units.decimal(units.gigabytes(used.abs_diff(baseline)), 1),
+                    units.memory_label
+                ),
+            )
+            .with_metric(
+                "ram_used_bytes",
+                "Physical memory in use in bytes.",
+                used as f64,
+            ),
+        );
+    } else if config.display.ram {
+        report.push(
+            Field::new(
+                "ram",
+                "Ram",
+                format!(
+                    "{} {}",
+                    get_total_memory_gb(system, &config.units),
+                    config.units.memory_label
+                ),
+            )
+            .with_metric(
+                "ram_bytes",
+                "Total physical memory in bytes.",
+                system.total_memory() as f64,
+            ),
+        );
+    }
+
+    if config.display.battery {
+        let field = match get_battery_info(config.battery_state_style)? {
+            Some(mut battery) => {
+                if config.display.battery_limit {
+                    if let Some(limit) = get_battery_charge_limit() {
+                        battery.description =
+                            format!("{} (Charge limit {}%)", battery.description, limit);
+                    }
+                }
+                Field::new("battery", "Battery", battery.description).with_metric(
+                    "battery_percent",
+                    "Charge of the first battery in percent.",
+                    battery.percentage.round() as f64,
+                )
+            }
+            None => Field::new(
+                "battery",
+                "Battery",
+                config.unavailable_text("Not detected").to_string(),
+            )
+            .undetected(),
+        };
+        report.push(field);
+    }
+
+    if config.display.battery && config.display.battery_include_peripherals {
+        for (name, charge) in get_peripheral_batteries() {
+            report.push(Field::new("peripheral_batteries", "Battery", charge).with_instance(name));
+        }
+    }
+
+    if config.display.threads {
+        if let Some(threads) = get_thread_count(system).and_then(|total| total.parse::<u64>().ok())
+        {
+            report.push(
+                Field::new(
+                    "threads",
+                    "Threads",
+                    format_number(threads, config.number_grouping),
+                )
+                .with_metric(
+                    "threads",
+                    "Threads across all processes.",
+                    threads as f64,
+                ),
+            );
+        }
+    }
+
+    if config.display.last_resume {
+        if let Some(last_resume) = background.take("last_resume", get_last_resume) {
+            report.push(Field::new("last_resume", "Last Resume", last_resume));
+        }
+    }
+
+    if config.display.swap {
+        if let (Some(swap), Some(percent)) = (
+            get_swap_usage(system, &config.units),
+            get_swap_percent(system),
+        ) {
+            report.push(
+                Field::new("swap", "Swap", swap)
+                    .with_metric(
+                        "swap_used_bytes",
+                        "Swap space in use in bytes.",
+                        system.used_swap() as f64,
+                    )
+                    .with_color(percentage_color(percent, config.theme.swap_warning)),
+            );
+        }
+    }
+
+    if config.display.mitigations {
+        if let Some(mitigations) = get_cpu_mitigations() {
+            report.push(Field::new("mitigations", "Mitigations", mitigations));
+        }
+    }
+
+    if config.display.open_fds {
+        if let Some(open_fds) = get_open_fds(config.number_grouping) {
+            report.push(Field::new("open_fds", "Open FDs", open_fds));
+        }
+    }
+
+    if config.display.power_profile {
+        if let Some(profile) = background.take("power_profile", get_power_profile) {
+            report.push(Field::new("power_profile", "Power Profile", profile));
+        }
+    }
+
+    if config.display.swappiness {
+        if let Some(swappiness) = get_swappiness() {
+            report.push(Field::new("swappiness", "Swappiness", swappiness));
+        }
+    }
+
+    if config.display.resolution {
+        if let Some(resolution) =
+            background.take("resolution", || get_resolution(config.display.refresh_rate))
+        {
+            report.push(Field::new("resolution", "Resolution", resolution));
+        }
+    }
+
+    if config.display.gpu_count {
+        if let Some(gpu_count) = get_gpu_count() {
+            report.push(Field::new("gpu_count", "GPUs", gpu_count));
+        }
+    }
+
+    if config.display.disks {
+        for (mount_point, usage, used) in get_disks(
+            config.display.disk_mount_opts,
+            config.disk_sort,
+            &config.units,
+        ) {
+            report.push(
+                Field::new("disks", "Disk", usage)
+                    .with_instance(mount_point)
+                    .with_metric(
+                        "disk_used_bytes",
+                        "Disk space in use in bytes, per mount point.",
+                        used as f64,
+                    ),
+            );
+        }
+    }
+
+    if config.display.gpu_clock {
+        if let Some(gpu_clock) = background.take("gpu_clock", get_gpu_clock) {
+            report.push(Field::new("gpu_clock", "GPU Clock", gpu_clock));
+        }
+    }
+
+    if config.display.machine_id {
+        if let Some(machine_id) = get_machine_id(config.display.machine_id_short) {
+            report.push(Field::new("machine_id", "Machine ID", machine_id));
+        }
+    }
+
+    if config.display.microcode {
+        if let Some(microcode) = get_microcode() {
+            report.push(Field::new("microcode", "Microcode", microcode));
+        }
+    }
+
+    if config.display.compositor {
+        if let Some(compositor) = get_compositor(system) {
+            report.push(Field::new("compositor", "Compositor", compositor));
+        }
+    }
+
+    if config.display.cpu_activity {
+        if let Some(activity) = background.take("cpu_activity", || get_cpu_activity(&config.units))
+        {
+            report.push(Field::new("cpu_activity", "CPU Activity", activity));
+        }
+    }
+
+    if config.display.fonts {
+        if let Some(fonts) = background.take("fonts", get_font_count) {
+            report.push(Field::new("fonts", "Fonts", fonts));
+        }
+    }
+
+    if config.display.local_ip {
+        if let Some(local_ip) = get_local_ip(config.local_ip_interface.as_deref()) {
+            report.push(Field::new("local_ip", "Local IP", local_ip));
+        }
+    }
+
+    if config.display.scale {
+        if let Some(scale) = background.take("scale", || get_display_scale(&config.units)) {
+            report.push(Field::new("scale", "Scale", scale));
+        }
+    }
+
+    if config.display.cstate {
+        if let Some(cstate) = get_cstate_info() {
+            report.push(Field::new("cstate", "C-State", cstate));
+        }
+    }
+
+    if config.display.brightness {
+        if let Some(brightness) = get_brightness() {
+            report.push(Field::new("brightness", "Brightness", brightness));
+        }
+    }
+
+    if config.display.cmdline {
+        if let Some(cmdline) = get_kernel_cmdline() {
+            report.push(Field::new("cmdline", "Cmdline", cmdline));
+        }
+    }
+
+    if config.display.zombies {
+        if let Some(zombies) = get_zombie_count(system) {
+            report.push(Field::new("zombies", "Zombies", zombies));
+        }
+    }
+
+    if config.display.gpu_processes {
+        if let Some(gpu_processes) =
+            background.take("gpu_processes", || get_gpu_processes(&config.units))
+        {
+            report.push(Field::new("gpu_processes", "GPU Processes", gpu_processes));
+        }
+    }
+
+    if config.display.trim {
+        if let Some(trim) = background.take("trim", get_trim_status) {
+            report.push(Field::new("trim", "TRIM", trim));
+        }
+    }
+
+    if config.display.ipv6 {
+        if let Some(ipv6) = get_ipv6_address() {
+            report.push(Field::new("ipv6", "IPv6", ipv6));
+        }
+    }
+
+    if config.display.firewall {
+        if let Some(firewall) = background.take("firewall", get_firewall_status) {
+            report.push(Field::new("firewall", "Firewall", firewall));
+        }
+    }
+
+    if config.display.chassis {
+        if let Some(chassis) = get_chassis_type() {
+            report.push(Field::new("chassis", "Chassis", chassis));
+        }
+    }
+
+    if config.display.browser {
+        if let Some(browser) = background.take("browser", get_default_browser) {
+            report.push(Field::new("browser", "Browser", browser));
+        }
+    }
+
+    if config.display.gpu_link {
+        if let Some(gpu_link) = get_gpu_link() {
+            report.push(Field::new("gpu_link", "GPU Link", gpu_link));
+        }
+    }
+
+    if config.display.audio_server {
+        if let Some(audio_server) = get_audio_server(system) {
+            report.push(Field::new("audio_server", "Audio Server", audio_server));
+        }
+    }
+
+    if config.display.temperature {
+        if let Some(temperature) = get_max_temperature() {
+            report.push(
+                Field::new("temperature", "Temp", format!("{:.0}°C", temperature)).with_metric(
+                    "temperature_celsius",
+                    "Hottest temperature sensor reading in degrees Celsius.",
+                    temperature as f64,
+                ),
+            );
+        }
+    }
+
+    if config.display.cpu_clocks {
+        if let Some(cpu_clocks) = get_cpu_clocks(&config.units) {
+            report.push(Field::new("cpu_clocks", "CPU Clocks", cpu_clocks));
+        }
+    }
+
+    if config.display.cores_active {
+        if let Some(cores) = get_cores_active() {
+            report.push(Field::new("cores_active", "Cores", cores));
+        }
+    }
+
+    if config.display.rng_source {
+        if let Some(rng) = get_rng_source() {
+            report.push(Field::new("rng_source", "RNG", rng));
+        }
+    }
+
+    if config.display.adapter_watts {
+        if let Some(adapter) = get_adapter_watts() {
+            report.push(Field::new("adapter_watts", "Adapter", adapter));
+        }
+    }
+
+    if config.display.volume {
+        if let Some(volume) = background.take("volume", get_volume) {
+            report.push(Field::new("volume", "Volume", volume));
+        }
+    }
+
+    if config.display.sandbox_packages {
+        if let Some(packages) = background.take("sandbox_packages", get_sandbox_packages) {
+            report.push(Field::new("sandbox_packages", "Sandboxed Apps", packages));
+        }
+    }
+
+    if config.display.hypervisor {
+        if let Some(hypervisor) = get_hypervisor() {
+            report.push(Field::new("hypervisor", "Hypervisor", hypervisor));
+        }
+    }
+
+    if config.display.connections {
+        if let Some(connections) = get_connection_count(config.number_grouping) {
+            report.push(Field::new("connections", "Connections", connections));
+        }
+    }
+
+    if config.display.cloud {
+        if let Some(cloud) = background.take("cloud", || {
+            get_cloud_provider(config.display.cloud_metadata)
+        }) {
+            report.push(Field::new("cloud", "Cloud", cloud));
+        }
+    }
+
+    if config.display.thermal_zones {
+        if let Some(zones) = get_thermal_zones() {
+            report.push(Field::new("thermal_zones", "Thermal", zones));
+        }
+    }
+
+    if config.display.cache_domains {
+        if let Some(domains) = get_cache_domains() {
+            report.push(Field::new("cache_domains", "L3 Domains", domains));
+        }
+    }
+
+    if config.display.gpu_driver {
+        if let Some(driver) = get_gpu_driver() {
+            report.push(Field::new("gpu_driver", "GPU Driver", driver));
+        }
+    }
+
+    if config.display.container_runtime {
+        if let Some(runtime) = get_container_runtime() {
+            report.push(Field::new(
+                "container_runtime",
+                "Container Runtime",
+                runtime,
+            ));
+        }
+    }
+
+    if config.display.self_sched {
+        if let Some(sched) = get_self_scheduling() {
+            report.push(Field::new("self_sched", "Sched", sched));
+        }
+    }
+
+    if config.display.audio_params {
+        if let Some(params) = background.take("audio_params", get_audio_params) {
+            report.push(Field::new("audio_params", "Audio", params));
+        }
+    }
+
+    if config.display.network_errors {
+        if let Some(errors) = get_network_errors() {
+            report.push(Field::new("network_errors", "Net Errors", errors));
+        }
+    }
+
+    if config.display.tpm {
+        if let Some(tpm) = get_tpm_info(config.display.tpm_show_absent) {
+            report.push(Field::new("tpm", "TPM", tpm));
+        }
+    }
+
+    if config.display.time {
+        report.push(Field::new(
+            "time",
+            "Time",
+            get_current_time(&config.time_format),
+        ));
+    }
+
+    if config.display.load_normalized {
+        if let Some(load) = get_load_normalized(system, &config.units) {
+            report.push(Field::new("load_normalized", "Load", load));
+        }
+    }
+
+    if config.display.io_scheduler {
+        if let Some(scheduler) = get_io_scheduler() {
+            report.push(Field::new("io_scheduler", "Scheduler", scheduler));
+        }
+    }
+
+    if config.display.top_memory {
+        if let Some(process) = get_top_memory_process(system, &config.units) {
+            report.push(Field::new("top_memory", "Top Memory", process));
+        }
+    }
+
+    if config.display.storage_model {
+        if let Some(model) = get_storage_model() {
+            report.push(Field::new("storage_model", "Storage Model", model));
+        }
+    }
+
+    for (index, custom) in config.custom.iter().enumerate() {
+        let output = background.take(&custom_key(index), || {
+            shell_output(&custom.command, custom_timeout(custom))
+        });
+        let field = match output {
+            Some(value) => Field::new("custom", custom.label.clone(), value),
+            None => Field::new(
+                "custom",
+                custom.label.clone(),
+                config.unavailable_text("Unavailable").to_string(),
+            )
+            .undetected(),
+        };
+        report.push(field);
+    }
+
+    for file_field in &config.file_field {
+        let field = match read_first_line(&file_field.path) {
+            Some(value) => Field::new("file_field", file_field.label.clone(), value),
+            None => Field::new(
+                "file_field",
+                file_field.label.clone(),
+                config.unavailable_text("Unavailable").to_string(),
+            )
+            .undetected(),
+        };
+        report.push(field);
+    }
+
+    cache.store(background.looked_up);
+
+    // Last so it reads as a verdict on everything above it.
+    if config.health_summary {
+        let (health, offender) = evaluate_health(system, &config.theme)?;
+        let status = match offender {
+            Some(offender) => format!("{} ({})", health.label(), offender),
+            None => health.label().to_string(),
+        };
+        let color = (health != Health::Ok).then(|| health.color());
+        report.push(Field::new("health_summary", "Status", status).with_color(color));
+    }
+
+    report.fields.retain(|field| {
+        !config
+            .hide_when
+            .get(field.key)
+            .is_some_and(|condition| condition.matches(field))
+    });
+
+    if !config.sections.is_empty() {
+        arrange_sections(&mut report, &config.sections);
+    }
+
+    if config.anonymize {
+        anonymize(&mut report);
+    }
+
+    Ok(report)
+}
+
+/// Mask the values of `SENSITIVE_FIELDS`, keeping placeholders like
+/// `Not detected` so the output still shows what was missing. The current
+/// user's name is masked wherever it is a component of a disk mount point.
+fn anonymize(report: &mut Report) {
+    let username = ["USER", "LOGNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()));
+    for field in &mut report.fields {
+        if field.detected && SENSITIVE_FIELDS.contains(&field.key) {
+            field.value = ANON_MASK.to_string();
+        }
+        if let (Some(username), "disks", Some(mount_point)) =
+            (&username, field.key, &mut field.instance)
+        {
+            *mount_point = mask_path_component(mount_point, username);
+        }
+    }
+}
+
+/// Replace every `/`-separated component of `path` equal to `name` with
+/// [`ANON_MASK`], e.g. `/home/alice/data` becomes `/home/***/data`.
+fn mask_path_component(path: &str, name: &str) -> String {
+    path.split('/')
+        .map(|component| {
+            if component == name {
+                ANON_MASK
+            } else {
+                component
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Unit suffixes recognized after a number when dimming units.
+const UNITS: &[&str] = &[
+    "GHz", "MHz", "Hz", "GiB", "GB", "Gb", "MiB", "MB", "°C", "W", "%",
+];
+
+/// Wrap every unit that directly follows a number (optionally after a space)
+/// in the ANSI dim attribute. `22` ends the dim without resetting the color.
+fn dim_units(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut after_digit = false;
+    let mut index = 0;
+    while let Some(c) = value[index..].chars().next() {
+        if after_digit {
+            let rest = &value[index..];
+            let spaced = rest.strip_prefix(' ').unwrap_or(rest);
+            let unit = UNITS.iter().find(|unit| {
+                spaced
+                    .strip_prefix(**unit)
+                    .is_some_and(|tail| !tail.starts_with(char::is_alphanumeric))
+            });
+            if let Some(unit) = unit {
+                let gap = rest.len() - spaced.len();
+                out.push_str(&rest[..gap]);
+                out.push_str(&format!("\x1b[2m{}\x1b[22m", unit));
+                index += gap + unit.len();
+                after_digit = false;
+                continue;
+            }
+        }
+        after_digit = c.is_ascii_digit();
+        out.push(c);
+        index += c.len_utf8();
+    }
+    out
+}
+
+/// Shorten `value` to at most `max_width` characters, ending in `…` when cut.
+fn truncate_value(value: &str, max_width: usize) -> String {
+    if value.chars().count() <= max_width {
+        return value.to_string();
+    }
+    let kept: String = value.chars().take(max_width.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+/// ASCII art shown alongside the text report when `logo_position` is set,
+/// unless `logo_override` replaces it.
+const LOGO: &[&str] = &[
+    "    .--.",
+    "   |o_o |",
+    "   |:_/ |",
+    "  //   \\ \\",
+    " (|     | )",
+    "/'\\_   _/`\\",
+    "\\___)=(___/",
+];
+
+/// Built-in logos that `logo_override` can select by name.
+const LOGOS: &[(&str, &[&str])] = &[("tux", LOGO)];
+
+/// Columns of blank space between the logo and the report when side by side.
+const LOGO_GAP: usize = 3;
+
+/// Width of `text` in terminal columns, ignoring ANSI escape sequences.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip to the final byte of the `ESC [ ... m` sequence.
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Place `logo` above, left of, or right of the report `text`, padding each
+/// side so the columns line up whichever block is taller. A top logo is
+/// divided from the report by `separator` repeated to the report's width.
+fn arrange_logo(logo: &[&str], text: &str, position: LogoPosition, separator: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if position == LogoPosition::Top {
+        let text_width = lines
+            .iter()
+            .map(|line| visible_width(line))
+            .max()
+            .unwrap_or(0);
+        // Measured without escapes so a colored separator isn't cut short.
+        let rule = match visible_width(separator) {
+            0 => String::new(),
+            width => separator.repeat((text_width / width).max(1)),
+        };
+        return format!("{}\n{}\n{}", logo.join("\n"), rule, text);
+    }
+    let pad =
+        |line: &str, width: usize| format!("{}{}", line, " ".repeat(width - visible_width(line)));
+    let logo_width = logo
+        .iter()
+        .map(|line| visible_width(line))
+        .max()
+        .unwrap_or(0);
+    let text_width = lines
+        .iter()
+        .map(|line| visible_width(line))
+        .max()
+        .unwrap_or(0);
+    let gap = " ".repeat(LOGO_GAP);
+    (0..logo.len().max(lines.len()))
+        .map(|row| {
+            let logo_line = logo.get(row).copied().unwrap_or_default();
+            let text_line = lines.get(row).copied().unwrap_or_default();
+            let joined = match position {
+                LogoPosition::Left => format!("{}{}{}", pad(logo_line, logo_width), gap, text_line),
+                _ => format!("{}{}{}", pad(text_line, text_width), gap, logo_line),
+            };
+            joined.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Surround `text` with a rounded box, optionally with `title` in the top
+/// edge. Lines are padded by visible width so colored values still line up.
+fn draw_border(text: &str, title: Option<&str>) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let title = title.map(|title| format!(" {} ", title));
+    let title_width = title.as_deref().map(visible_width).unwrap_or(0);
+    let width = lines
+        .iter()
+        .map(|line| visible_width(line))
+        .max()
+        .unwrap_or(0)
+        .max(title_width.saturating_sub(1));
+    // The inner width includes one space of padding on each side.
+    let inner = width + 2;
+    let top = match &title {
+        Some(title) => format!("╭─{}{}╮", title, "─".repeat(inner - 1 - title_width)),
+        None => format!("╭{}╮", "─".repeat(inner)),
+    };
+    let mut boxed = vec![top];
+    for line in lines {
+        let padding = " ".repeat(width - visible_width(line));
+        boxed.push(format!("│ {}{} │", line, padding));
+    }
+    boxed.push(format!("╰{}╯", "─".repeat(inner)));
+    boxed.join("\n")
+}
+
+/// Languages whose locales write decimals with a comma (`3,5`).
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "bg", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "id", "it", "lt", "lv", "nb",
+    "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr", "uk", "vi",
+];
+
+/// Decimal separator for the `locale` setting: `C` (the default) keeps `.`,
+/// `system` follows `LC_ALL`, `LC_NUMERIC`, or `LANG`, and anything else is
+/// read as a locale name such as `de_DE.UTF-8`.
+fn decimal_separator(locale: &str) -> char {
+    let locale = if locale == "system" {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default()
+    } else {
+        locale.to_string()
+    };
+    let language = locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default();
+    if DECIMAL_COMMA_LANGUAGES.contains(&language.to_lowercase().as_str()) {
+        ','
+    } else {
+        '.'
+    }
+}
+
+/// Narrowest value column `max_width` will wrap into.
+const MIN_WRAP_WIDTH: usize = 10;
+
+/// Break `text` into lines of at most `width` characters at spaces. Words
+/// longer than a whole line are split where they overflow.
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let mut word = word.to_string();
+        loop {
+            let needed = if line.is_empty() { 0 } else { 1 } + word.chars().count();
+            if line.chars().count() + needed <= width {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&word);
+                break;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+                continue;
+            }
+            // An overlong word fills the line and carries over.
+            let head: String = word.chars().take(width).collect();
+            word = word.chars().skip(width).collect();
+            lines.push(head);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Put each section's title above its lines, with a blank line between
+/// sections. Sections left empty are skipped entirely.
+fn with_section_titles(lines: Vec<String>, sections: &[(String, usize)]) -> Vec<String> {
+    if sections.is_empty() {
+        return lines;
+    }
+    let mut lines = lines.into_iter();
+    let mut out = Vec::new();
+    for (title, count) in sections {
+        if *count == 0 {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push(String::new());
+        }
+        out.push(title.clone());
+        out.extend(lines.by_ref().take(*count));
+    }
+    // Lines outside every section, such as the `health_summary` verdict.
+    let rest: Vec<String> = lines.collect();
+    if !rest.is_empty() && !out.is_empty() {
+        out.push(String::new());
+    }
+    out.extend(rest);
+    out
+}
+
+/// Render the report as `Label: value` lines under the optional summary
+/// header, boxed when `border` is set and with the logo placed per
+/// `logo_position`.
+fn render_text(report: &Report, config: &Config) -> String {
+    let theme = &config.theme;
+    let dim = theme.colors && theme.dim_units;
+    let separator = if dim { "\x1b[2m:\x1b[22m" } else { ":" };
+    // Columns left for the report once the logo and border take their share.
+    let report_width = config.max_width.and_then(MaxWidth::columns).map(|width| {
+        let logo = match config.logo_position {
+            Some(LogoPosition::Left | LogoPosition::Right) => {
+                config
+                    .logo()
+                    .iter()
+                    .map(|line| visible_width(line))
+                    .max()
+                    .unwrap_or(0)
+                    + LOGO_GAP
+            }
+            _ => 0,
+        };
+        let border = if config.border { 4 } else { 0 };
+        width.saturating_sub(logo + border)
+    });
+    let lines: Vec<String> = report
+        .fields
+        .iter()
+        .map(|field| {
+            let value = match config.max_value_width {
+                Some(max_width) => truncate_value(&field.value, max_width),
+                None => field.value.clone(),
+            };
+            let label = field.display_label();
+            // `Label: ` hangs over wrapped lines, which align under the value.
+            let indent = label.chars().count() + 2;
+            // Long labels (e.g. deep mount points) leave too little room to
+            // wrap usefully, so those values stay on one line.
+            let lines = match report_width.map(|width| width.saturating_sub(indent)) {
+                Some(room) if room >= MIN_WRAP_WIDTH => wrap_words(&value, room),
+                _ => vec![value],
+            };
+            let lines: Vec<String> = lines
+                .into_iter()
+                .map(|line| {
+                    let line = if dim { dim_units(&line) } else { line };
+                    match field.color {
+                        Some(color) if theme.colors => format!("\x1b[{}m{}\x1b[0m", color, line),
+                        _ => line,
+                    }
+                })
+                .collect();
+            // Reset only the background so the label's width is unchanged.
+            let label = match theme.label_bg {
+                Some(background) if theme.colors => {
+                    format!("\x1b[{}m{}\x1b[49m", background.background_sgr(), label)
+                }
+                _ => label,
+            };
+            format!(
+                "{}{} {}",
+                label,
+                separator,
+                lines.join(&format!("\n{}", " ".repeat(indent)))
+            )
+        })
+        .collect();
+    let text = with_section_titles(lines, &report.sections).join("\n");
+    let text = match &report.header {
+        Some(header) if text.is_empty() => header.clone(),
+        Some(header) => format!("{}\n{}", header, text),
+        None => text,
+    };
+    let text = if config.border && !text.is_empty() {
+        draw_border(&text, config.border_title.as_deref())
+    } else {
+        text
+    };
+    match config.logo_position {
+        Some(position) if !text.is_empty() => {
+            arrange_logo(&config.logo(), &text, position, &config.logo_separator)
+        }
+        _ => text,
+    }
+}
+
+/// Escape a Prometheus label value (backslash, double quote, and newline).
+fn escape_prometheus_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A gauge's name and help text with each sample's rendered labels and value.
+type GaugeFamily = (String, String, Vec<(String, f64)>);
+
+/// Render the report in the Prometheus text exposition format. Fields with a
+/// numeric reading become gauges, one family per metric name with samples
+/// told apart by `instance` (e.g. the mount point of each disk) or, for
+/// fields without a metric of their own such as custom ones, `label`. The
+/// rest are labels on `retch_info`. Undetected fields are left out.
+fn render_prometheus(report: &Report) -> String {
+    let mut lines = Vec::new();
+    // Samples grouped by metric name, in order of first appearance.
+    let mut families: Vec<GaugeFamily> = Vec::new();
+    // Fields that repeat (e.g. one per disk) share a single label.
+    let mut info_labels: Vec<(&str, Vec<&str>)> = Vec::new();
+
+    for field in report.fields.iter().filter(|field| field.detected) {
+        let mut labels = Vec::new();
+        let (name, help, value) = match &field.metric {
+            Some(metric) => (
+                metric.name.to_string(),
+                metric.help.to_string(),
+                metric.value,
+            ),
+            None => match field.value.parse::<f64>() {
+                Ok(value) => {
+                    labels.push(format!(
+                        "label=\"{}\"",
+                        escape_prometheus_label(&field.label)
+                    ));
+                    (
+                        field.key.to_string(),
+                        format!("{} reported by retch.", field.label),
+                        value,
+                    )
+                }
+                Err(_) => {
+                    match info_labels.iter_mut().find(|(key, _)| *key == field.key) {
+                        Some((_, values)) => values.push(&field.value),
+                        None => info_labels.push((field.key, vec![&field.value])),
+                    }
+                    continue;
+                }
+            },
+        };
+        if let Some(instance) = &field.instance {
+            labels.push(format!(
+                "instance=\"{}\"",
+                escape_prometheus_label(instance)
+            ));
+        }
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", labels.join(","))
+        };
+        match families.iter_mut().find(|(family, _, _)| *family == name) {
+            Some((_, _, samples)) => samples.push((labels, value)),
+            None => families.push((name, help, vec![(labels, value)])),
+        }
+    }
+
+    for (name, help, samples) in families {
+        lines.push(format!("# HELP retch_{} {}", name, help));
+        lines.push(format!("# TYPE retch_{} gauge", name));
+        for (labels, value) in samples {
+            lines.push(format!("retch_{}{} {}", name, labels, value));
+        }
+    }
+
+    if !info_labels.is_empty() {
+        lines.push("# HELP retch_info Non-numeric system facts reported by retch.".to_string());
+        lines.push("# TYPE retch_info gauge".to_string());
+        let labels: Vec<String> = info_labels
+            .iter()
+            .map(|(key, values)| {
+                format!(
+                    "{}=\"{}\"",
+                    key,
+                    escape_prometheus_label(&values.join("; "))
+                )
+            })
+            .collect();
+        lines.push(format!("retch_info{{{}}} 1", labels.join(",")));
+    }
+
+    lines.join("\n")
+}
+
+/// Render the report as pretty-printed JSON.
+fn render_json(report: &Report) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(serde_json::to_string_pretty(report)?)
+}
+
+/// Render the report as a TOML document keyed like the JSON output.
+fn render_toml(report: &Report) -> Result<String, Box<dyn std::error::Error>> {
+    let document = toml::to_string(&toml::Value::try_from(report)?)?;
+    Ok(document.trim_end().to_string())
+}
+
+/// Escape text for use in HTML element content and attribute values.
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Render the report as an HTML table. Each row carries a `retch-<key>` class
+/// (with underscores turned into dashes) so pages can style fields individually.
+fn render_html(report: &Report) -> String {
+    let mut lines = vec!["<table class=\"retch\">".to_string()];
+    for field in &report.fields {
+        lines.push(format!(
+            "  <tr class=\"retch-{}\"><th>{}</th><td>{}</td></tr>",
+            field.key.replace('_', "-"),
+            escape_html(&field.display_label()),
+            escape_html(&field.value)
+        ));
+    }
+    lines.push("</table>".to_string());
+    lines.join("\n")
+}
+
+/// Header and opening of the endless array that starts an i3bar stream.
+const I3BAR_HEADER: &str = "{\"version\": 1}\n[";
+
+/// Hex equivalent of an ANSI SGR color for i3bar blocks.
+fn ansi_to_hex(color: &str) -> Option<&'static str> {
+    match color {
+        ANSI_RED => Some("#FF0000"),
+        ANSI_YELLOW => Some("#FFFF00"),
+        ANSI_GREEN => Some("#00FF00"),
+        _ => None,
+    }
+}
+
+/// Render the report as one i3bar status line: a JSON array with a block per
+/// field, terminated by a comma so lines can be streamed after [`I3BAR_HEADER`].
+fn render_i3bar(report: &Report, config: &Config) -> String {
+    let blocks = report
+        .fields
+        .iter()
+        .map(|field| {
+            let mut block = serde_json::Map::new();
+            let mut insert = |key: &str, value: &str| {
+                block.insert(
+                    key.to_string(),
+                    serde_json::Value::String(value.to_string()),
+                );
+            };
+            insert("name", field.key);
+            if let Some(instance) = &field.instance {
+                insert("instance", instance);
+            }
+            insert(
+                "full_text",
+                &format!("{}: {}", field.display_label(), field.value),
+            );
+            insert("short_text", &field.value);
+            let color = field
+                .color
+                .filter(|_| config.theme.colors)
+                .and_then(ansi_to_hex);
+            if let Some(color) = color {
+                insert("color", color);
+            }
+            serde_json::Value::Object(block)
+        })
+        .collect();
+    format!("{},", serde_json::Value::Array(blocks))
+}
+
+/// Command-line flags.
+#[derive(Debug, Default)]
+struct Args {
+    /// Print a single status glyph and exit with a matching code.
+    glyph: bool,
+    /// Redraw the report every `watch_interval` seconds until interrupted.
+    watch: bool,
+    /// While watching, re-read the config whenever the file changes.
+    reload_on_change: bool,
+    /// Report which fields this platform supports, without their values.
+    probe: bool,
+    /// Show text output through `$PAGER` when writing to a terminal.
+    pager: bool,
+    /// Record the current used memory as the `ram_baseline` and exit.
+    save_mem_baseline: bool,
+    /// Answer report requests on this Unix socket instead of printing once.
+    serve: Option<PathBuf>,
+    /// Mask identifying values so the output can be shared.
+    anon: bool,
+    /// Compare against the `log_file` entry from at least this long ago.
+    since: Option<Duration>,
+    /// Print this field's value as a QR code instead of the report.
+    qr: Option<String>,
+    /// Write the default config to `CONFIG_PATH` (or `--config`) and exit.
+    init: bool,
+    /// Let `--init` replace an existing config.
+    force: bool,
+    /// Print every field's `[Display]` key and exit.
+    list_fields: bool,
+    /// Exit quietly, with success, when no fields are enabled.
+    quiet: bool,
+    /// Config file to read instead of `CONFIG_PATH`; a `.json` extension
+    /// selects JSON.
+    config: Option<String>,
+}
+
+/// Parse the process arguments, rejecting anything unrecognized.
+fn parse_args() -> Result<Args, Box<dyn std::error::Error>> {
+    let mut args = Args::default();
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--glyph" => args.glyph = true,
+            "--watch" => args.watch = true,
+            "--reload-on-change" => args.reload_on_change = true,
+            "--probe" => args.probe = true,
+            "--pager" => args.pager = true,
+            "--init" => args.init = true,
+            "--list-fields" => args.list_fields = true,
+            "--quiet" => args.quiet = true,
+            "--force" => args.force = true,
+            "--anon" => args.anon = true,
+            "--config" => match argv.next() {
+                Some(path) => args.config = Some(path),
+                None => return Err("--config expects a file path".into()),
+            },
+            "--serve" => match argv.next() {
+                Some(path) => args.serve = Some(PathBuf::from(path)),
+                None => return Err("--serve expects a socket path".into()),
+            },
+            "--qr" => match argv.next() {
+                Some(key) if FIELDS.contains(&resolve_field_name(&key)) => {
+                    args.qr = Some(resolve_field_name(&key).to_string())
+                }
+                Some(key) => return Err(format!("--qr: unknown field `{}`", key).into()),
+                None => return Err("--qr expects a field name such as `local_ip`".into()),
+            },
+            "--since" => match argv.next().as_deref().and_then(parse_span) {
+                Some(span) => args.since = Some(span),
+                None => return Err("--since expects a span such as `30m`, `1h`, or `2d`".into()),
+            },
+            "--mem-baseline" => match argv.next().as_deref() {
+                Some("save") => args.save_mem_baseline = true,
+                _ => return Err("--mem-baseline expects `save`".into()),
+            },
+            other => return Err(format!("unknown argument: {}", other).into()),
+        }
+    }
+    if args.reload_on_change && !args.watch {
+        return Err("--reload-on-change requires --watch".into());
+    }
+    if args.force && !args.init {
+        return Err("--force requires --init".into());
+    }
+    if args.init && args.config.as_deref().is_some_and(is_json_path) {
+        return Err("--init writes a TOML config; give --config a .toml path".into());
+    }
+    Ok(args)
+}
+
+/// Parse a span like `90s`, `30m`, `1h`, `2d`, or `1w`.
+fn parse_span(text: &str) -> Option<Duration> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let amount: u64 = text[..split].parse().ok()?;
+    let unit = match &text[split..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return None,
+    };
+    Some(Duration::from_secs(amount * unit))
+}
+
+/// Location of the configuration file.
+const CONFIG_PATH: &str = "src/.config.toml";
+
+/// Render the report in the configured output format.
+fn render_report(report: &Report, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match config.output_format {
+        OutputFormat::Text => render_text(report, config),
+        OutputFormat::Prometheus => render_prometheus(report),
+        OutputFormat::Json => render_json(report)?,
+        OutputFormat::Html => render_html(report),
+        OutputFormat::I3bar => render_i3bar(report, config),
+        OutputFormat::Toml => render_toml(report)?,
+    })
+}
+
+/// Try every field and print `key: ok` or `key: unavailable` for each, so users
+/// can share what their platform supports without leaking the values.
+fn probe(config: Config, system: &mut System) -> Result<(), Box<dyn std::error::Error>> {
+    // Sections would drop the fields they don't list, and [HideWhen] the
+    // values it matches.
+    let config = Config {
+        display: DisplayConfig::all(),
+        sections: Vec::new(),
+        hide_when: HashMap::new(),
+        ..config
+    };
+    let report = gather_report(&config, system)?;
+    for key in FIELDS {
+        let available = report
+            .fields
+            .iter()
+            .any(|field| field.key == *key && field.detected);
+        let status = if available { "ok" } else { "unavailable" };
+        println!("{}: {}", key, status);
+    }
+    Ok(())
+}
+
+/// Gather only the field `key` and print its value as a Unicode QR code with
+/// the value underneath, for scanning with a phone.
+fn print_qr(
+    config: Config,
+    system: &mut System,
+    key: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use qrcode::render::unicode::Dense1x2;
+
+    let config = Config {
+        display: DisplayConfig::only(key),
+        sections: Vec::new(),
+        hide_when: HashMap::new(),
+        ..config
+    };
+    let report = gather_report(&config, system)?;
+    let value = report
+        .fields
+        .iter()
+        .find(|field| field.key == key && field.detected)
+        .map(|field| field.value.clone())
+        .ok_or_else(|| format!("{} is not available on this system", key))?;
+    let code = qrcode::QrCode::new(value.as_bytes())?;
+    // Inverted so the code reads light-on-dark, as most terminals are dark.
+    let image = code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .build();
+    println!("{}\n{}", image, value);
+    Ok(())
+}
+
+/// Pipe `output` through `$PAGER` (default `less -R`, so colors survive).
+/// Falls back to printing directly if the pager can't be started.
+fn page(output: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string());
+    let mut words = pager.split_whitespace();
+    let child = words.next().and_then(|program| {
+        Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .spawn()
+            .ok()
+    });
+    let Some(mut child) = child else {
+        println!("{}", output);
+        return Ok(());
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything.
+        let _ = writeln!(stdin, "{}", output);
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Where `--mem-baseline save` records used memory, under the XDG state
+/// directory (`~/.local/state` by default).
+fn mem_baseline_path() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(state_home.join("retch/mem-baseline"))
+}
+
+/// Store the current used memory, in bytes, as the baseline.
+fn save_mem_baseline(system: &System) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = mem_baseline_path().ok_or("no HOME or XDG_STATE_HOME to store the baseline")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, format!("{}\n", system.used_memory()))?;
+    Ok(path)
+}
+
+/// The used memory recorded by `--mem-baseline save`, if any.
+fn read_mem_baseline() -> Option<u64> {
+    fs::read_to_string(mem_baseline_path()?)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Column names of the `log_file` CSV, written when the file is created.
+const LOG_HEADER: &str = "timestamp,ram_used_bytes,disk_used_bytes,uptime_seconds";
+
+/// One row of the `log_file` CSV: when a run happened and the figures
+/// `--since` compares.
+struct LogEntry {
+    /// Seconds since the Unix epoch.
+    timestamp: u64,
+    ram_used: u64,
+    /// Used space summed over every mounted disk.
+    disk_used: u64,
+    uptime: u64,
+}
+
+impl LogEntry {
+    fn current(system: &System) -> Self {
+        let disk_used = Disks::new_with_refreshed_list()
+            .iter()
+            .map(|disk| disk.total_space().saturating_sub(disk.available_space()))
+            .sum();
+        LogEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |now| now.as_secs()),
+            ram_used: system.used_memory(),
+            disk_used,
+            uptime: System::uptime(),
+        }
+    }
+
+    /// Parse a CSV row, or `None` for the header and malformed lines.
+    fn parse(line: &str) -> Option<Self> {
+        let mut columns = line.split(',').map(|column| column.trim().parse::<u64>());
+        let mut next = || columns.next()?.ok();
+        Some(LogEntry {
+            timestamp: next()?,
+            ram_used: next()?,
+            disk_used: next()?,
+            uptime: next()?,
+        })
+    }
+}
+
+/// Append `entry` to the log at `path`, starting a new file with the header.
+fn append_log(path: &Path, entry: &LogEntry) -> io::Result<()> {
+    let is_new = fs::metadata(path).map_or(true, |meta| meta.len() == 0);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    if is_new {
+        writeln!(file, "{}", LOG_HEADER)?;
+    }
+    writeln!(
+        file,
+        "{},{},{},{}",
+        entry.timestamp, entry.ram_used, entry.disk_used, entry.uptime
+    )
+}
+
+/// The newest logged entry that is at least `span` older than `now`, i.e. the
+/// nearest one before the start of the window. A missing log has no entries.
+fn find_log_entry(
+    path: &Path,
+    now: u64,
+    span: Duration,
+) -> Result<Option<LogEntry>, Box<dyn std::error::Error>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let cutoff = now.saturating_sub(span.as_secs());
+    Ok(content
+        .lines()
+        .filter_map(LogEntry::parse)
+        .filter(|entry| entry.timestamp <= cutoff)
+        .max_by_key(|entry| entry.timestamp))
+}
+
+/// Append fields showing how RAM, disk, and uptime changed from `past` to
+/// `current`, e.g. `RAM Change: +0.4 Gb over 1h 2m`.
+fn push_since_fields(
+    report: &mut Report,
+    current: &LogEntry,
+    past: &LogEntry,
+    units: &UnitsConfig,
+) {
+    let over = format_duration(current.timestamp.saturating_sub(past.timestamp));
+    let change = |now: u64, then: u64| {
+        let sign = if now < then { "-" } else { "+" };
+        format!(
+            "{}{} {} over {}",
+            sign,
+            units.decimal(units.gigabytes(now.abs_diff(then)), 1),
+            units.memory_label,
+            over
+        )
+    };
+    report.push(Field::new(
+        "ram_change",
+        "RAM Change",
+        change(current.ram_used, past.ram_used),
+    ));
+    report.push(Field::new(
+        "disk_change",
+        "Disk Change",
+        change(current.disk_used, past.disk_used),
+    ));
+    // Uptime only shrinks across a reboot.
+    let uptime = if current.uptime < past.uptime {
+        "rebooted".to_string()
+    } else {
+        format!("+{}", format_duration(current.uptime - past.uptime))
+    };
+    report.push(Field::new("uptime_change", "Uptime Change", uptime));
+}
+
+/// Last modification time of the config, used to detect edits while watching.
+fn config_modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Fold this frame's temperature into `peak` and append the peak to the
+/// temperature field, e.g. `58°C (max 74°C)`.
+fn track_temperature_peak(report: &mut Report, peak: &mut Option<f64>) {
+    let Some(field) = report
+        .fields
+        .iter_mut()
+        .find(|field| field.key == "temperature")
+    else {
+        return;
+    };
+    let Some(current) = field.metric.as_ref().map(|metric| metric.value) else {
+        return;
+    };
+    let max = peak.map_or(current, |peak| peak.max(current));
+    *peak = Some(max);
+    field.value = format!("{} (max {:.0}°C)", field.value, max);
+}
+
+/// Append whether swap use grew (`↑`), shrank (`↓`), or held (`=`) since the
+/// previous frame, remembering this frame's reading in `previous`.
+fn track_swap_trend(report: &mut Report, previous: &mut Option<f64>) {
+    let Some(field) = report.fields.iter_mut().find(|field| field.key == "swap") else {
+        return;
+    };
+    let Some(current) = field.metric.as_ref().map(|metric| metric.value) else {
+        return;
+    };
+    if let Some(previous) = previous.replace(current) {
+        let arrow = match current.total_cmp(&previous) {
+            std::cmp::Ordering::Greater => "↑",
+            std::cmp::Ordering::Less => "↓",
+            std::cmp::Ordering::Equal => "=",
+        };
+        field.value = format!("{} {}", field.value, arrow);
+    }
+}
+
+/// Redraw the report in place until interrupted, optionally picking up config
+/// edits by polling the file's modification time between frames. State that
+/// spans frames, such as the temperature peak and swap trend, lives here.
+fn watch(
+    mut config: Config,
+    config_path: &str,
+    system: &mut System,
+    reload_on_change: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut last_modified = config_modified(config_path);
+    let mut temperature_peak = None;
+    let mut previous_swap = None;
+    let mut i3bar_started = false;
+    let mut cache = FieldCache::default();
+    loop {
+        system.refresh_memory();
+        system.refresh_cpu_all();
+        let mut report = gather_report_cached(&config, system, &mut cache)?;
+        if config.display.temperature_peak {
+            track_temperature_peak(&mut report, &mut temperature_peak);
+        }
+        if config.display.swap_trend {
+            track_swap_trend(&mut report, &mut previous_swap);
+        }
+
+        let mut stdout = io::stdout().lock();
+        if config.output_format == OutputFormat::I3bar {
+            // i3bar reads one status line per frame after a single header.
+            if !i3bar_started {
+                writeln!(stdout, "{}", I3BAR_HEADER)?;
+                i3bar_started = true;
+            }
+            write!(stdout, "{}", render_report(&report, &config)?)?;
+        } else {
+            // Clear the screen and move the cursor home before each frame.
+            write!(stdout, "\x1b[2J\x1b[H{}", render_report(&report, &config)?)?;
+        }
+        writeln!(stdout)?;
+        stdout.flush()?;
+        drop(stdout);
+
+        thread::sleep(Duration::from_secs_f64(config.watch_interval.max(0.1)));
+
+        if reload_on_change {
+            let modified = config_modified(config_path);
+            if modified != last_modified {
+                last_modified = modified;
+                // Keep the previous settings if the edit left the file invalid.
+                match load_config(config_path, false) {
+                    Ok(reloaded) => {
+                        config = Config {
+                            anonymize: config.anonymize,
+                            ..reloaded
+                        };
+                        // Custom fields are tracked by position, which edits can shift.
+                        cache = FieldCache::default();
+                    }
+                    Err(err) => eprintln!("failed to reload config: {}", err),
+                }
+            }
+        }
+    }
+}
+
+/// Listen on a Unix socket and answer each connection with a freshly gathered
+/// report. A client may send one line naming an output format (e.g. `json`);
+/// an empty line, or none within a second, uses the configured format.
+#[cfg(unix)]
+fn serve(
+    mut config: Config,
+    system: &mut System,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+
+    // A socket left behind by an earlier run would make `bind` fail.
+    if fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let default_format = config.output_format;
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("failed to accept connection: {}", err);
+                continue;
+            }
+        };
+        stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+        let mut request = String::new();
+        // A timeout just means the client didn't ask for a format.
+        let _ = BufReader::new(&stream).read_line(&mut request);
+        let response = match request.trim() {
+            "" => Ok(default_format),
+            format => OutputFormat::deserialize(toml::Value::String(format.to_lowercase()))
+                .map_err(|_| format!("unknown output format: {}", format)),
+        }
+        .and_then(|output_format| {
+            system.refresh_memory();
+            system.refresh_cpu_all();
+            config.output_format = output_format;
+            let report = gather_report(&config, system).map_err(|err| err.to_string())?;
+            render_report(&report, &config).map_err(|err| err.to_string())
+        });
+        let response = response.unwrap_or_else(|err| format!("error: {}", err));
+        // The client may hang up without reading.
+        let _ = writeln!(stream, "{}", response);
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn serve(_: Config, _: &mut System, _: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    Err("--serve needs Unix domain sockets, which this platform lacks".into())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = parse_args()?;
+    let config_path = args.config.as_deref().unwrap_or(CONFIG_PATH);
+    if args.init {
+        if init_config(config_path, args.force)? {
+            println!("Created {}", config_path);
+        } else {
+            println!(
+                "Skipped {}: it already exists (use --force to overwrite)",
+                config_path
+            );
+        }
+        return Ok(());
+    }
+    if args.list_fields {
+        for key in FIELDS {
+            println!("{}", key);
+        }
+        return Ok(());
+    }
+    // Only the implicit path may be missing; a path the user gave must exist.
+    let mut config = load_config(config_path, args.config.is_none())?;
+    config.anonymize = args.anon;
+
+    let mut system = System::new();
+    // Refresh the data every report uses; the process table is only loaded
+    // when a field that needs it is enabled.
+    system.refresh_memory();
+    system.refresh_cpu_all();
+
+    if args.glyph {
+        let (health, _) = evaluate_health(&system, &config.theme)?;
+        if config.theme.colors {
+            println!("\x1b[{}m{}\x1b[0m", health.color(), health.glyph());
+        } else {
+            println!("{}", health.glyph());
+        }
+        std::process::exit(health.exit_code());
+    }
+
+    if args.save_mem_baseline {
+        let path = save_mem_baseline(&system)?;
+        println!("Saved memory baseline to {}", path.display());
+        return Ok(());
+    }
+
+    if args.probe {
+        return probe(config, &mut system);
+    }
+
+    if let Some(key) = &args.qr {
+        return print_qr(config, &mut system, key);
+    }
+
+    if args.watch {
+        return watch(config, config_path, &mut system, args.reload_on_change);
+    }
+
+    if let Some(path) = &args.serve {
+        return serve(config, &mut system, path);
+    }
+
+    // Printing nothing for a config with every field switched off would
+    // look like a broken binary. An enabled report that comes out empty
+    // (nothing detected, or all hidden) is fine.
+    if !config.has_enabled_fields() {
+        if args.quiet {
+            return Ok(());
+        }
+        eprintln!("no fields enabled; see --list-fields");
+        std::process::exit(1);
+    }
+
+    let mut report = gather_report(&config, &mut system)?;
+    if args.since.is_some() || config.log_file.is_some() {
+        let entry = LogEntry::current(&system);
+        if let Some(span) = args.since {
+            let path = config
+                .log_file
+                .as_deref()
+                .ok_or("--since needs `log_file` set in the config")?;
+            match find_log_entry(path, entry.timestamp, span)? {
+                Some(past) => push_since_fields(&mut report, &entry, &past, &config.units),
+                None => eprintln!("no entry in {} is that old yet", path.display()),
+            }
+        }
+        if let Some(path) = &config.log_file {
+            append_log(path, &entry)?;
+        }
+    }
+    let output = render_report(&report, &config)?;
+
+    // Paging only makes sense for humans: skip it when piped or for
+    // machine-readable formats.
+    let use_pager =
+        args.pager && io::stdout().is_terminal() && config.output_format == OutputFormat::Text;
+    if config.output_format == OutputFormat::I3bar {
+        println!("{}", I3BAR_HEADER);
+    }
+    if use_pager && !output.is_empty() {
+        page(&output)?;
+    } else if !output.is_empty() {
+        println!("{}", output);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distribution(content: &str, detailed: bool) -> Option<String> {
+        distribution_name(&parse_os_release(content), detailed)
+    }
+
+    #[test]
+    fn distribution_appends_capitalized_codename() {
+        let content =
+            "NAME=\"Ubuntu\"\nPRETTY_NAME=\"Ubuntu 22.04.3 LTS\"\nVERSION_CODENAME=jammy\n";
+        assert_eq!(
+            distribution(content, true).as_deref(),
+            Some("Ubuntu 22.04.3 LTS (Jammy)")
+        );
+        assert_eq!(
+            distribution(content, false).as_deref(),
+            Some("Ubuntu 22.04.3 LTS")
+        );
+    }
+
+    #[test]
+    fn distribution_skips_codename_already_in_name() {
+        let content = "PRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\nVERSION_CODENAME=bookworm\n";
+        assert_eq!(
+            distribution(content, true).as_deref(),
+            Some("Debian GNU/Linux 12 (bookworm)")
+        );
+    }
+
+    #[test]
+    fn distribution_appends_build_id() {
+        let content = "PRETTY_NAME=\"Arch Linux\"\nBUILD_ID=rolling\n";
+        assert_eq!(
+            distribution(content, true).as_deref(),
+            Some("Arch Linux build rolling")
+        );
+    }
+
+    #[test]
+    fn distribution_without_codename_or_build_id() {
+        let content = "NAME=\"Gentoo\"\nPRETTY_NAME=\"Gentoo Linux\"\nVERSION_CODENAME=\n";
+        assert_eq!(distribution(content, true).as_deref(), Some("Gentoo Linux"));
+    }
+
+    #[test]
+    fn distribution_needs_pretty_name() {
+        assert_eq!(distribution("NAME=Linux\n", true), None);
+    }
+
+    #[test]
+    fn json_has_a_key_per_detected_field() {
+        let keys: Vec<&'static str> = FIELDS
+            .iter()
+            .copied()
+            .chain(SUB_FIELDS.iter().map(|(sub, _, _)| *sub))
+            .chain(OTHER_REPORT_KEYS.iter().copied())
+            .chain(SINCE_KEYS.iter().copied())
+            .collect();
+        let mut report = Report::default();
+        for key in &keys {
+            let field = Field::new(key, "Label", "value".to_string());
+            let field = match *key {
+                "battery" => field.undetected(),
+                "disks" | "peripheral_batteries" => field.with_instance("first".to_string()),
+                _ => field,
+            };
+            report.push(field);
+        }
+        report.push(
+            Field::new("disks", "Disk", "second".to_string()).with_instance("/home".to_string()),
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&render_json(&report).unwrap()).unwrap();
+        let object = json.as_object().unwrap();
+        let expected: Vec<&str> = keys.into_iter().filter(|key| *key != "battery").collect();
+        assert_eq!(
+            object.keys().map(String::as_str).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(object["ram_used"], "value");
+        assert_eq!(
+            object["disks"],
+            serde_json::json!([
+                {"name": "first", "value": "value"},
+                {"name": "/home", "value": "second"},
+            ])
+        );
+        assert_eq!(
+            object["custom"],
+            serde_json::json!([{"name": "Label", "value": "value"}])
+        );
+    }
+
+    #[test]
+    fn prometheus_groups_samples_into_one_family() {
+        let mut report = Report::default();
+        for (mount_point, used) in [("/", 10.0), ("/home", 2.0)] {
+            report.push(
+                Field::new("disks", "Disk", String::new())
+                    .with_instance(mount_point.to_string())
+                    .with_metric("disk_used_bytes", "Disk space in use.", used),
+            );
+        }
+        report.push(Field::new("custom", "Jobs", "3".to_string()));
+        report.push(Field::new("custom", "Queue", "7".to_string()));
+        report.push(Field::new("battery", "Battery", "Not detected".to_string()).undetected());
+        report.push(Field::new("os", "OS", "Linux".to_string()));
+        assert_eq!(
+            render_prometheus(&report),
+            [
+                "# HELP retch_disk_used_bytes Disk space in use.",
+                "# TYPE retch_disk_used_bytes gauge",
+                "retch_disk_used_bytes{instance=\"/\"} 10",
+                "retch_disk_used_bytes{instance=\"/home\"} 2",
+                "# HELP retch_custom Jobs reported by retch.",
+                "# TYPE retch_custom gauge",
+                "retch_custom{label=\"Jobs\"} 3",
+                "retch_custom{label=\"Queue\"} 7",
+                "# HELP retch_info Non-numeric system facts reported by retch.",
+                "# TYPE retch_info gauge",
+                "retch_info{os=\"Linux\"} 1",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn json_config_drops_null_members() {
+        let value =
+            parse_json_config(r#"{"Units": {"memory_base": null, "memory_label": "GB"}}"#).unwrap();
+        let units = value["Units"].as_table().unwrap();
+        assert!(!units.contains_key("memory_base"));
+        assert_eq!(units["memory_label"].as_str(), Some("GB"));
+        assert!(parse_json_config(r#"{"custom": [null]}"#).is_err());
+    }
+
+    #[test]
+    fn threads_include_each_main_thread() {
+        assert_eq!(sum_threads([Some(0), Some(3), None]), Some(5));
+        assert_eq!(sum_threads([None, None]), None);
+        assert_eq!(sum_threads([]), None);
+    }
+
+    #[test]
+    fn background_fields_match_the_lookups_started() {
+        let config = Config {
+            display: DisplayConfig::all(),
+            ..toml::from_str(DEFAULT_CONFIG).unwrap()
+        };
+        let mut background = Background::default();
+        start_background_lookups(&config, &mut background);
+        let mut started: Vec<&str> = background
+            .pending
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !key.starts_with("custom."))
+            .collect();
+        started.sort();
+        let mut expected = BACKGROUND_FIELDS.to_vec();
+        expected.sort();
+        assert_eq!(started, expected);
+    }
+
+    #[test]
+    fn mount_points_mask_the_username() {
+        assert_eq!(mask_path_component("/home/alice", "alice"), "/home/***");
+        assert_eq!(
+            mask_path_component("/media/alice/USB alice", "alice"),
+            "/media/***/USB alice"
+        );
+        assert_eq!(mask_path_component("/", "alice"), "/");
+    }
+
+    #[test]
+    fn ghz_rounds_to_spec_sheet_value() {
+        let units = UnitsConfig::default();
+        assert_eq!(format_ghz(2594, true, &units), "2.6 GHz");
+        assert_eq!(format_ghz(2594, false, &units), "2.59 GHz");
+        assert_eq!(format_ghz(3000, true, &units), "3.0 GHz");
+        let units = UnitsConfig {
+            decimal_separator: decimal_separator("de_DE.UTF-8"),
+            ..UnitsConfig::default()
+        };
+        assert_eq!(format_ghz(2594, true, &units), "2,6 GHz");
+    }
 }